@@ -0,0 +1,117 @@
+use crate::filter::{should_prune_entry, should_skip_file, SecretRules};
+use crate::selector::{rel_slash, Selector};
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+pub(crate) fn is_tar_archive(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    name.ends_with(".tar") || name.ends_with(".tar.gz") || name.ends_with(".tgz")
+}
+
+// Regular-file members that pass the same name, hidden and selector filters as a
+// directory walk, sorted by member name. mtime and attribute filters don't apply.
+pub(crate) fn archive_files(
+    archive: &Path,
+    include_hidden: bool,
+    excluded_names: &[&str],
+    secrets: &SecretRules,
+    selector: &Selector,
+) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+    let file = File::open(archive)
+        .with_context(|| format!("failed to open archive: {}", archive.display()))?;
+
+    let name = archive.to_string_lossy().to_ascii_lowercase();
+    let reader: Box<dyn Read> = if name.ends_with(".tar") {
+        Box::new(file)
+    } else {
+        Box::new(GzDecoder::new(file))
+    };
+
+    let mut files = read_tar(reader, include_hidden, excluded_names, secrets, selector)
+        .with_context(|| format!("failed to read archive: {}", archive.display()))?;
+    files.sort_by(|(a, _), (b, _)| a.as_os_str().cmp(b.as_os_str()));
+    Ok(files)
+}
+
+fn read_tar(
+    reader: impl Read,
+    include_hidden: bool,
+    excluded_names: &[&str],
+    secrets: &SecretRules,
+    selector: &Selector,
+) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+    let mut files = Vec::new();
+    let mut tar = tar::Archive::new(reader);
+
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let Some(rel) = member_path(&entry.path()?) else {
+            continue;
+        };
+
+        let pruned = rel
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .any(|c| should_prune_entry(&c.as_os_str().to_string_lossy(), true, include_hidden));
+        if pruned || should_skip_file(&rel, include_hidden, excluded_names, secrets).is_some() {
+            continue;
+        }
+
+        if !selector.matches(&rel_slash(&rel)) {
+            continue;
+        }
+
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes)?;
+        files.push((rel, bytes));
+    }
+
+    Ok(files)
+}
+
+// Normalizes `./src/lib.rs` to `src/lib.rs` and rejects members that escape the archive root.
+pub(crate) fn member_path(path: &Path) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    for c in path.components() {
+        match c {
+            Component::Normal(part) => out.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!out.as_os_str().is_empty()).then_some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn member_path_strips_dot_and_rejects_escapes() {
+        assert_eq!(
+            member_path(Path::new("./src/lib.rs")),
+            Some(PathBuf::from("src/lib.rs"))
+        );
+        assert_eq!(member_path(Path::new("../etc/passwd")), None);
+        assert_eq!(member_path(Path::new("/abs")), None);
+    }
+
+    #[test]
+    fn is_tar_archive_matches_tar_and_gzipped_tar() {
+        assert!(is_tar_archive(Path::new("snap.tar")));
+        assert!(is_tar_archive(Path::new("snap.TAR.GZ")));
+        assert!(is_tar_archive(Path::new("snap.tgz")));
+        assert!(!is_tar_archive(Path::new("snap.zip")));
+    }
+}
@@ -0,0 +1,91 @@
+use crate::dump::{sha256_hex, DumpReport};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// One cached dump, named by a hash of everything that went into it.
+pub(crate) struct CacheEntry {
+    path: PathBuf,
+}
+
+impl CacheEntry {
+    // `parts` must cover every input that can change the dump (options, config, files).
+    pub(crate) fn new(dir: &Path, parts: &[&str]) -> Self {
+        let key = sha256_hex(parts.join("\0").as_bytes());
+        Self {
+            path: dir.join(key),
+        }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // The report sits in a sidecar file; an entry missing either half is a miss.
+    fn report_path(&self) -> PathBuf {
+        self.path.with_extension("report.json")
+    }
+
+    pub(crate) fn load(&self) -> Option<(Vec<u8>, DumpReport)> {
+        let report = serde_json::from_slice(&fs::read(self.report_path()).ok()?).ok()?;
+        Some((fs::read(&self.path).ok()?, report))
+    }
+
+    pub(crate) fn store(&self, bytes: &[u8], report: &DumpReport) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create cache dir: {}", dir.display()))?;
+        }
+        let report = serde_json::to_vec(report).context("failed to serialize dump report")?;
+        write_atomic(&self.report_path(), &report)?;
+        write_atomic(&self.path, bytes)
+    }
+}
+
+// Written to a temp file first so a concurrent run never reads half a file.
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&tmp, bytes)
+        .and_then(|()| fs::rename(&tmp, path))
+        .with_context(|| format!("failed to write cache: {}", path.display()))
+}
+
+// $XDG_CACHE_HOME/dumpo, else ~/.cache/dumpo.
+pub(crate) fn default_cache_dir() -> Option<PathBuf> {
+    let xdg = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute());
+    let base = xdg.or_else(|| {
+        std::env::var_os("HOME")
+            .filter(|h| !h.is_empty())
+            .map(|h| PathBuf::from(h).join(".cache"))
+    })?;
+    Some(base.join("dumpo"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempRepo;
+
+    #[test]
+    fn entry_round_trips_and_keys_on_every_part() {
+        let dir = TempRepo::new();
+        let entry = CacheEntry::new(dir.path(), &["v1", "opts", "files"]);
+        assert!(entry.load().is_none());
+
+        let report = DumpReport {
+            files_included: 3,
+            warnings: vec!["skipping a.rs: denied".to_string()],
+            ..Default::default()
+        };
+        entry.store(b"dump", &report).unwrap();
+        let (bytes, loaded) = entry.load().unwrap();
+        assert_eq!(bytes, b"dump");
+        assert_eq!(loaded.files_included, 3);
+        assert_eq!(loaded.warnings, report.warnings);
+
+        let other = CacheEntry::new(dir.path(), &["v1", "opts", "files2"]);
+        assert_ne!(other.path(), entry.path());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::dump::for_each_dumped_file;
+use crate::pack::resolve_quietly;
+use crate::tokens::estimate_tokens;
+use crate::{CheckArgs, PackArgs};
+use anyhow::Result;
+
+// What the repo would cost if dumped whole (no per-file or total truncation).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CheckSummary {
+    pub(crate) files: usize,
+    pub(crate) bytes: usize,
+    pub(crate) tokens: usize,
+}
+
+pub(crate) fn run_check(args: CheckArgs) -> Result<()> {
+    let summary = measure(&args)?;
+    let line = format!(
+        "{} files, {} bytes, ~{} tokens",
+        summary.files, summary.bytes, summary.tokens
+    );
+
+    if let Some(over) = over_budget(&args, &summary) {
+        anyhow::bail!("over budget: {line} ({over})");
+    }
+
+    eprintln!("dumpo: fits: {line}");
+    Ok(())
+}
+
+fn over_budget(args: &CheckArgs, summary: &CheckSummary) -> Option<String> {
+    if let Some(max) = args.max_total_tokens {
+        if summary.tokens > max {
+            return Some(format!("max_total_tokens={max}"));
+        }
+    }
+    if let Some(max) = args.max_total_bytes {
+        if summary.bytes > max {
+            return Some(format!("max_total_bytes={max}"));
+        }
+    }
+    None
+}
+
+// Resolved exactly like `pack` (config, profiles, filters), so both agree on the files.
+fn measure(args: &CheckArgs) -> Result<CheckSummary> {
+    let pack_args = PackArgs {
+        path: args.path.clone(),
+        include: args.include.clone(),
+        exclude: args.exclude.clone(),
+        include_hidden: args.include_hidden.then_some(true),
+        config: args.config.clone(),
+        profile: args.profile.clone(),
+        no_config: args.no_config,
+        ..Default::default()
+    };
+    let (root, mut opts, selector) = resolve_quietly(&pack_args)?;
+    // Files are counted whole, so there's no budget to share out between directories.
+    opts.per_dir_byte_cap = None;
+    opts.balance_dirs = false;
+
+    let mut summary = CheckSummary::default();
+    for_each_dumped_file(&root, &opts, &selector, |bytes| {
+        summary.files += 1;
+        summary.bytes += bytes.len();
+        summary.tokens += estimate_tokens(&String::from_utf8_lossy(bytes));
+    })?;
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempRepo;
+
+    fn check_args(repo: &TempRepo, max_total_tokens: usize) -> CheckArgs {
+        CheckArgs {
+            path: repo.path().to_path_buf(),
+            max_total_tokens: Some(max_total_tokens),
+            no_config: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn check_passes_at_budget_and_fails_just_above_it() {
+        let repo = TempRepo::new();
+        repo.write("a.rs", &"a".repeat(400));
+        repo.write("b.rs", &"b".repeat(400));
+
+        let summary = measure(&check_args(&repo, 0)).unwrap();
+        assert_eq!(summary.files, 2);
+        assert_eq!(summary.tokens, 200);
+
+        assert!(run_check(check_args(&repo, 200)).is_ok());
+
+        let err = run_check(check_args(&repo, 199)).unwrap_err();
+        assert!(err.to_string().starts_with("over budget: 2 files"));
+    }
+
+    #[test]
+    fn check_measures_the_files_pack_would_dump() {
+        let repo = TempRepo::new();
+        let elsewhere = TempRepo::new();
+        repo.write("a.rs", &"a".repeat(400));
+        repo.write("b.rs", &"b".repeat(400));
+        repo.write(
+            "big.bin.rs",
+            "version https://git-lfs.github.com/spec/v1\noid sha256:00\n",
+        );
+        elsewhere.write("dumpo.toml", "exclude = [\"b.rs\"]\n");
+
+        let args = CheckArgs {
+            config: Some(elsewhere.path().join("dumpo.toml")),
+            no_config: false,
+            ..check_args(&repo, 0)
+        };
+        let summary = measure(&args).unwrap();
+        assert_eq!(summary.files, 1);
+        assert_eq!(summary.bytes, 400);
+    }
+}
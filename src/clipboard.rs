@@ -1,28 +1,114 @@
 use anyhow::{Context, Result};
 use std::io::Write;
 use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
 
-pub(crate) fn copy_to_clipboard(bytes: &[u8]) -> Result<()> {
+// pbcopy can hang while another process holds the pasteboard; give up after this long.
+pub(crate) const DEFAULT_CLIPBOARD_TIMEOUT: Duration = Duration::from_secs(5);
+
+// How often a running copy command is checked for exit.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// No clipboard backend exists on this machine, as opposed to one that failed mid-copy.
+#[derive(Debug, Clone)]
+pub(crate) struct ClipboardUnavailable(pub(crate) String);
+
+impl std::fmt::Display for ClipboardUnavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ClipboardUnavailable {}
+
+pub(crate) fn copy_to_clipboard(bytes: &[u8], timeout: Duration) -> Result<()> {
     if !cfg!(target_os = "macos") {
-        anyhow::bail!("clipboard copy is only supported on macOS (pbcopy) right now");
+        return Err(ClipboardUnavailable(
+            "clipboard copy is only supported on macOS (pbcopy) right now".to_string(),
+        )
+        .into());
     }
 
-    let mut child = Command::new("pbcopy")
+    pipe_to_command("pbcopy", &[], bytes, timeout)
+}
+
+// Feeds `bytes` to `program` on stdin and waits for it to exit, killing it after `timeout`.
+fn pipe_to_command(program: &str, args: &[&str], bytes: &[u8], timeout: Duration) -> Result<()> {
+    let mut child = match Command::new(program)
+        .args(args)
         .stdin(Stdio::piped())
         .spawn()
-        .context("failed to spawn pbcopy (is pbcopy available?)")?;
-
     {
-        let mut stdin = child.stdin.take().context("failed to open pbcopy stdin")?;
-        stdin
-            .write_all(bytes)
-            .context("failed writing to pbcopy stdin")?;
-    }
+        Ok(child) => child,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(ClipboardUnavailable(format!("{program} not found")).into());
+        }
+        Err(e) => return Err(e).with_context(|| format!("failed to spawn {program}")),
+    };
+
+    // Written from a thread so a command that stops reading can't block us past the timeout.
+    let mut stdin = child
+        .stdin
+        .take()
+        .with_context(|| format!("failed to open {program} stdin"))?;
+    let input = bytes.to_vec();
+    let writer = thread::spawn(move || stdin.write_all(&input));
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child
+            .try_wait()
+            .with_context(|| format!("failed to wait for {program}"))?
+        {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            // Killing closes the pipe, so the writer thread finishes too.
+            let _ = child.kill();
+            let _ = child.wait();
+            let _ = writer.join();
+            anyhow::bail!(
+                "{program} did not finish within {:.1}s (another process may be holding the clipboard); killed it",
+                timeout.as_secs_f64()
+            );
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
 
-    let status = child.wait().context("failed to wait for pbcopy")?;
+    writer
+        .join()
+        .map_err(|_| anyhow::anyhow!("{program} stdin writer panicked"))?
+        .with_context(|| format!("failed writing to {program} stdin"))?;
     if !status.success() {
-        anyhow::bail!("pbcopy failed");
+        anyhow::bail!("{program} failed");
     }
 
     Ok(())
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slow_copy_command_is_killed_after_timeout() {
+        let start = Instant::now();
+        let err = pipe_to_command("sleep", &["10"], b"dump", Duration::from_millis(100))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("did not finish within 0.1s"), "{err}");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn fast_copy_command_succeeds() {
+        pipe_to_command(
+            "sh",
+            &["-c", "cat >/dev/null"],
+            b"dump",
+            Duration::from_secs(5),
+        )
+        .unwrap();
+    }
+}
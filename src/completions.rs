@@ -0,0 +1,32 @@
+use crate::Cli;
+use anyhow::{Context, Result};
+use clap::CommandFactory;
+use clap_complete::Shell;
+use std::io::{self, Write};
+
+pub(crate) fn run_completions(shell: Shell) -> Result<()> {
+    let mut out = io::stdout().lock();
+    write_completions(shell, &mut out);
+    out.flush().context("failed writing to stdout")
+}
+
+fn write_completions(shell: Shell, out: &mut dyn Write) {
+    let mut cmd = Cli::command();
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(shell, &mut cmd, name, out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bash_completions_mention_pack_subcommand() {
+        let mut buf = Vec::new();
+        write_completions(Shell::Bash, &mut buf);
+        let s = String::from_utf8(buf).unwrap();
+
+        assert!(!s.is_empty());
+        assert!(s.contains("pack"));
+    }
+}
@@ -1,39 +1,232 @@
+use crate::dump::{MaxFilesMode, TotalLimitMode};
+use crate::filter::SecretRules;
+use crate::format::{FenceChar, OutputFormat};
 use anyhow::{Context, Result};
+use indexmap::IndexMap;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Default, Clone, Deserialize)]
 pub(crate) struct DumpoConfig {
     pub(crate) max_file_bytes: Option<usize>,
+    // Per-extension caps that take precedence over max_file_bytes, e.g. { json = 100 }.
+    pub(crate) max_file_bytes_by_ext: Option<HashMap<String, usize>>,
+    // Per-language caps keyed on the fence language (e.g. { rust = 40000 }); extension caps win.
+    pub(crate) max_file_bytes_by_language: Option<HashMap<String, usize>>,
     pub(crate) max_total_bytes: Option<usize>,
+    pub(crate) total_limit_mode: Option<TotalLimitMode>,
+    // Guardrail on the number of selected files; see max_files_mode.
+    pub(crate) max_files: Option<usize>,
+    pub(crate) max_files_mode: Option<MaxFilesMode>,
     pub(crate) include_hidden: Option<bool>,
+    // Hidden paths to dump anyway when include_hidden is off, e.g. [".github/**"].
+    pub(crate) allow_hidden: Option<Vec<String>>,
+    pub(crate) max_replacement_ratio: Option<f64>,
+    pub(crate) binary_sample_bytes: Option<usize>,
+    pub(crate) max_read_bytes: Option<u64>,
+    // Directory names that mark probable fixtures, and the cap --trim-fixtures cuts them to.
+    pub(crate) fixture_dirs: Option<Vec<String>>,
+    pub(crate) fixture_max_bytes: Option<usize>,
+    // Extra secret file names, name prefixes and extensions; the built-ins always apply.
+    pub(crate) secret_filenames: Option<Vec<String>>,
+    pub(crate) secret_prefixes: Option<Vec<String>>,
+    pub(crate) secret_exts: Option<Vec<String>>,
+    // Tuning for --scan-entropy.
+    pub(crate) entropy_threshold: Option<f64>,
+    pub(crate) entropy_min_len: Option<usize>,
     pub(crate) include: Option<Vec<String>>,
     pub(crate) exclude: Option<Vec<String>>,
+    // Match include/exclude against paths relative to this file's directory, not the root.
+    pub(crate) globs_relative_to_config: Option<bool>,
+    // Markers checked by --skip-generated; replaces the built-in list.
+    pub(crate) generated_markers: Option<Vec<String>>,
+    pub(crate) truncation_footer: Option<String>,
+    pub(crate) file_truncated_marker: Option<String>,
+    pub(crate) format: Option<OutputFormat>,
+    pub(crate) fence_char: Option<FenceChar>,
+    // Glob -> fence language, e.g. { "*.conf" = "ini" }; first match (in file order) wins.
+    pub(crate) language_overrides: Option<IndexMap<String, String>>,
+    pub(crate) file_heading_prefix: Option<String>,
+    // Fail instead of emitting a dump with no files in it.
+    pub(crate) error_on_empty: Option<bool>,
+    pub(crate) stdout: Option<bool>,
+    pub(crate) clipboard: Option<bool>,
+    // Seconds to wait for the clipboard command before killing it.
+    pub(crate) clipboard_timeout_secs: Option<u64>,
+    // Named partial configs selected with --profile, e.g. [profiles.slim].
+    pub(crate) profiles: Option<HashMap<String, DumpoConfig>>,
 }
 
 impl DumpoConfig {
+    pub(crate) fn secret_rules(&self) -> SecretRules {
+        SecretRules::with_extra(
+            self.secret_filenames.clone().unwrap_or_default(),
+            self.secret_prefixes.clone().unwrap_or_default(),
+            self.secret_exts.clone().unwrap_or_default(),
+        )
+    }
+
+    // This config with the keys set in profile `name` replacing its own.
+    pub(crate) fn with_profile(mut self, name: &str) -> Result<DumpoConfig> {
+        let mut profiles = self.profiles.take().unwrap_or_default();
+        let Some(profile) = profiles.remove(name) else {
+            let mut known: Vec<_> = profiles.into_keys().collect();
+            known.sort();
+            anyhow::bail!(
+                "unknown profile {name:?} (defined: {})",
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                }
+            );
+        };
+        Ok(self.overlay(profile))
+    }
+
+    // Destructured so a new key can't be forgotten here; profiles don't nest.
+    fn overlay(self, profile: DumpoConfig) -> DumpoConfig {
+        let DumpoConfig {
+            max_file_bytes,
+            max_file_bytes_by_ext,
+            max_file_bytes_by_language,
+            max_total_bytes,
+            total_limit_mode,
+            max_files,
+            max_files_mode,
+            include_hidden,
+            allow_hidden,
+            max_replacement_ratio,
+            binary_sample_bytes,
+            max_read_bytes,
+            fixture_dirs,
+            fixture_max_bytes,
+            secret_filenames,
+            secret_prefixes,
+            secret_exts,
+            entropy_threshold,
+            entropy_min_len,
+            include,
+            exclude,
+            globs_relative_to_config,
+            generated_markers,
+            truncation_footer,
+            file_truncated_marker,
+            format,
+            fence_char,
+            language_overrides,
+            file_heading_prefix,
+            error_on_empty,
+            stdout,
+            clipboard,
+            clipboard_timeout_secs,
+            profiles: _,
+        } = profile;
+
+        DumpoConfig {
+            max_file_bytes: max_file_bytes.or(self.max_file_bytes),
+            max_file_bytes_by_ext: max_file_bytes_by_ext.or(self.max_file_bytes_by_ext),
+            max_file_bytes_by_language: max_file_bytes_by_language
+                .or(self.max_file_bytes_by_language),
+            max_total_bytes: max_total_bytes.or(self.max_total_bytes),
+            total_limit_mode: total_limit_mode.or(self.total_limit_mode),
+            max_files: max_files.or(self.max_files),
+            max_files_mode: max_files_mode.or(self.max_files_mode),
+            include_hidden: include_hidden.or(self.include_hidden),
+            allow_hidden: allow_hidden.or(self.allow_hidden),
+            max_replacement_ratio: max_replacement_ratio.or(self.max_replacement_ratio),
+            binary_sample_bytes: binary_sample_bytes.or(self.binary_sample_bytes),
+            max_read_bytes: max_read_bytes.or(self.max_read_bytes),
+            fixture_dirs: fixture_dirs.or(self.fixture_dirs),
+            fixture_max_bytes: fixture_max_bytes.or(self.fixture_max_bytes),
+            secret_filenames: secret_filenames.or(self.secret_filenames),
+            secret_prefixes: secret_prefixes.or(self.secret_prefixes),
+            secret_exts: secret_exts.or(self.secret_exts),
+            entropy_threshold: entropy_threshold.or(self.entropy_threshold),
+            entropy_min_len: entropy_min_len.or(self.entropy_min_len),
+            include: include.or(self.include),
+            exclude: exclude.or(self.exclude),
+            globs_relative_to_config: globs_relative_to_config.or(self.globs_relative_to_config),
+            generated_markers: generated_markers.or(self.generated_markers),
+            truncation_footer: truncation_footer.or(self.truncation_footer),
+            file_truncated_marker: file_truncated_marker.or(self.file_truncated_marker),
+            format: format.or(self.format),
+            fence_char: fence_char.or(self.fence_char),
+            language_overrides: language_overrides.or(self.language_overrides),
+            file_heading_prefix: file_heading_prefix.or(self.file_heading_prefix),
+            error_on_empty: error_on_empty.or(self.error_on_empty),
+            stdout: stdout.or(self.stdout),
+            clipboard: clipboard.or(self.clipboard),
+            clipboard_timeout_secs: clipboard_timeout_secs.or(self.clipboard_timeout_secs),
+            profiles: None,
+        }
+    }
+
     pub(crate) fn load_nearest(root: &Path) -> Result<(Option<PathBuf>, DumpoConfig)> {
         let cfg_path = find_nearest_config_path(root);
         let Some(path) = cfg_path.clone() else {
             return Ok((None, DumpoConfig::default()));
         };
 
-        let s = fs::read_to_string(&path)
+        let cfg = DumpoConfig::load_file(&path)?;
+        Ok((Some(path), cfg))
+    }
+
+    // The format is picked from the extension; anything unrecognized is read as TOML.
+    pub(crate) fn load_file(path: &Path) -> Result<DumpoConfig> {
+        let s = fs::read_to_string(path)
             .with_context(|| format!("failed to read config: {}", path.display()))?;
 
-        let cfg: DumpoConfig = toml::from_str(&s)
-            .with_context(|| format!("failed to parse config: {}", path.display()))?;
+        let parsed = match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml" | "yml") => serde_yaml::from_str(&s).map_err(anyhow::Error::from),
+            Some("json") => serde_json::from_str(&s).map_err(anyhow::Error::from),
+            _ => toml::from_str(&s).map_err(anyhow::Error::from),
+        };
 
-        Ok((Some(path), cfg))
+        parsed.with_context(|| format!("failed to parse config: {}", path.display()))
     }
 }
 
+// Within one directory, earlier names win: toml > yaml > yml > json.
+pub(crate) const CONFIG_FILENAMES: [&str; 4] =
+    ["dumpo.toml", "dumpo.yaml", "dumpo.yml", "dumpo.json"];
+
+// Allowlist of include globs, one per line (`#` comments), found like dumpo.toml.
+pub(crate) const INCLUDE_FILENAME: &str = ".dumpoinclude";
+
+pub(crate) fn load_include_file(root: &Path) -> Result<Option<(PathBuf, Vec<String>)>> {
+    let Some(path) = root
+        .ancestors()
+        .map(|dir| dir.join(INCLUDE_FILENAME))
+        .find(|p| p.is_file())
+    else {
+        return Ok(None);
+    };
+
+    let patterns = read_pattern_file(&path)?;
+    Ok(Some((path, patterns)))
+}
+
+// One glob per line; blank lines and `#` comments are skipped.
+pub(crate) fn read_pattern_file(path: &Path) -> Result<Vec<String>> {
+    let s =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(s.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
 fn find_nearest_config_path(root: &Path) -> Option<PathBuf> {
     for dir in root.ancestors() {
-        let p = dir.join("dumpo.toml");
-        if p.is_file() {
-            return Some(p);
+        for name in CONFIG_FILENAMES {
+            let p = dir.join(name);
+            if p.is_file() {
+                return Some(p);
+            }
         }
     }
     None
@@ -55,6 +248,37 @@ mod tests {
         assert_eq!(cfg.max_total_bytes, Some(123));
     }
 
+    #[test]
+    fn profile_overrides_only_the_keys_it_sets() {
+        let repo = TempRepo::new();
+        repo.write(
+            "dumpo.toml",
+            "max_total_bytes = 1000\nmax_file_bytes = 50\n\n\
+             [profiles.slim]\nmax_total_bytes = 300\n",
+        );
+
+        let (_, cfg) = DumpoConfig::load_nearest(repo.path()).unwrap();
+        let slim = cfg.clone().with_profile("slim").unwrap();
+        assert_eq!(slim.max_total_bytes, Some(300));
+        assert_eq!(slim.max_file_bytes, Some(50));
+
+        let err = cfg.with_profile("full").unwrap_err();
+        assert_eq!(err.to_string(), "unknown profile \"full\" (defined: slim)");
+    }
+
+    #[test]
+    fn language_overrides_keep_file_order() {
+        let repo = TempRepo::new();
+        repo.write(
+            "dumpo.toml",
+            "[language_overrides]\n\"z/*.conf\" = \"text\"\n\"*.conf\" = \"ini\"\n",
+        );
+
+        let (_, cfg) = DumpoConfig::load_nearest(repo.path()).unwrap();
+        let patterns: Vec<String> = cfg.language_overrides.unwrap().into_keys().collect();
+        assert_eq!(patterns, ["z/*.conf", "*.conf"]);
+    }
+
     #[test]
     fn load_nearest_prefers_closer_config() {
         let repo = TempRepo::new();
@@ -70,4 +294,47 @@ mod tests {
         let (_path, cfg) = DumpoConfig::load_nearest(&nested).unwrap();
         assert_eq!(cfg.max_total_bytes, Some(222));
     }
+
+    #[test]
+    fn load_nearest_reads_yaml_config() {
+        let repo = TempRepo::new();
+        repo.write("dumpo.yaml", "max_total_bytes: 123\n");
+
+        let (path, cfg) = DumpoConfig::load_nearest(repo.path()).unwrap();
+        assert!(path.unwrap().ends_with("dumpo.yaml"));
+        assert_eq!(cfg.max_total_bytes, Some(123));
+    }
+
+    #[test]
+    fn load_nearest_reads_yml_config() {
+        let repo = TempRepo::new();
+        repo.write("dumpo.yml", "max_total_bytes: 124\n");
+
+        let (_path, cfg) = DumpoConfig::load_nearest(repo.path()).unwrap();
+        assert_eq!(cfg.max_total_bytes, Some(124));
+    }
+
+    #[test]
+    fn load_nearest_reads_json_config() {
+        let repo = TempRepo::new();
+        repo.write("dumpo.json", "{\"max_total_bytes\": 125}\n");
+
+        let (path, cfg) = DumpoConfig::load_nearest(repo.path()).unwrap();
+        assert!(path.unwrap().ends_with("dumpo.json"));
+        assert_eq!(cfg.max_total_bytes, Some(125));
+    }
+
+    #[test]
+    fn load_nearest_prefers_toml_then_yaml_then_json_in_one_dir() {
+        let repo = TempRepo::new();
+        repo.write("dumpo.json", "{\"max_total_bytes\": 3}\n");
+        repo.write("dumpo.yaml", "max_total_bytes: 2\n");
+
+        let (_path, cfg) = DumpoConfig::load_nearest(repo.path()).unwrap();
+        assert_eq!(cfg.max_total_bytes, Some(2));
+
+        repo.write("dumpo.toml", "max_total_bytes = 1\n");
+        let (_path, cfg) = DumpoConfig::load_nearest(repo.path()).unwrap();
+        assert_eq!(cfg.max_total_bytes, Some(1));
+    }
 }
@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+// Parses short human durations like "90s", "30m", "24h", "7d" or "2w".
+pub(crate) fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("missing unit in duration {s:?} (use s, m, h, d or w)"))?;
+    let (num, unit) = s.split_at(split);
+
+    let n: u64 = num
+        .parse()
+        .map_err(|_| format!("invalid number in duration {s:?}"))?;
+
+    let secs_per_unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "unknown unit {unit:?} in duration {s:?} (use s, m, h, d or w)"
+            ))
+        }
+    };
+
+    n.checked_mul(secs_per_unit)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("duration {s:?} is too large"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_accepts_supported_units() {
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("30m").unwrap(), Duration::from_secs(30 * 60));
+        assert_eq!(
+            parse_duration("24h").unwrap(),
+            Duration::from_secs(24 * 3600)
+        );
+        assert_eq!(
+            parse_duration("7d").unwrap(),
+            Duration::from_secs(7 * 86400)
+        );
+        assert_eq!(
+            parse_duration("2w").unwrap(),
+            Duration::from_secs(14 * 86400)
+        );
+    }
+
+    #[test]
+    fn parse_duration_rejects_malformed_input() {
+        assert!(parse_duration("24").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("3y").is_err());
+        assert!(parse_duration("-1d").is_err());
+    }
+}
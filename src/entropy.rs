@@ -0,0 +1,119 @@
+use std::ops::Range;
+
+pub(crate) const DEFAULT_ENTROPY_THRESHOLD: f64 = 4.5;
+pub(crate) const DEFAULT_ENTROPY_MIN_LEN: usize = 20;
+
+const REDACTED: &[u8] = b"[REDACTED]";
+
+// What --scan-entropy does with a file holding a high-entropy string.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum EntropyAction {
+    // Replace each flagged string with [REDACTED] and keep the file.
+    #[default]
+    Redact,
+    // Leave the file out of the dump.
+    Skip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct EntropyScan {
+    // Bits per byte a quoted string must exceed to be flagged.
+    pub(crate) threshold: f64,
+    // Shorter strings are never flagged.
+    pub(crate) min_len: usize,
+    pub(crate) action: EntropyAction,
+}
+
+impl EntropyScan {
+    // Byte ranges (quotes excluded) of quoted, whitespace-free strings that look like tokens.
+    pub(crate) fn find(&self, bytes: &[u8]) -> Vec<Range<usize>> {
+        quoted_strings(bytes)
+            .filter(|r| r.len() >= self.min_len)
+            .filter(|r| !bytes[r.clone()].iter().any(u8::is_ascii_whitespace))
+            .filter(|r| shannon_entropy(&bytes[r.clone()]) > self.threshold)
+            .collect()
+    }
+}
+
+pub(crate) fn redact(bytes: &[u8], ranges: &[Range<usize>]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut last = 0;
+    for r in ranges {
+        out.extend_from_slice(&bytes[last..r.start]);
+        out.extend_from_slice(REDACTED);
+        last = r.end;
+    }
+    out.extend_from_slice(&bytes[last..]);
+    out
+}
+
+fn shannon_entropy(bytes: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+    for &b in bytes {
+        counts[usize::from(b)] += 1;
+    }
+
+    let len = bytes.len() as f64;
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+// Contents of '...' and "..." strings on a single line; backslash escapes are skipped.
+fn quoted_strings(bytes: &[u8]) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut i = 0;
+    std::iter::from_fn(move || {
+        while i < bytes.len() {
+            let quote = bytes[i];
+            i += 1;
+            if quote != b'"' && quote != b'\'' {
+                continue;
+            }
+
+            let start = i;
+            while i < bytes.len() && bytes[i] != quote && bytes[i] != b'\n' {
+                i += if bytes[i] == b'\\' { 2 } else { 1 };
+            }
+            if i < bytes.len() && bytes[i] == quote {
+                i += 1;
+                return Some(start..i - 1);
+            }
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_base64_blob_but_not_a_sentence() {
+        let scan = EntropyScan {
+            threshold: DEFAULT_ENTROPY_THRESHOLD,
+            min_len: DEFAULT_ENTROPY_MIN_LEN,
+            action: EntropyAction::Redact,
+        };
+        let src = concat!(
+            "let token = \"dGhpcyBpcyBhIHNlY3JldCB0b2tlbiBrZXkgMTIzNDU2Nzg5MA==\";\n",
+            "let msg = \"Hello, this is a perfectly normal sentence.\";\n",
+            "let short = 'aZ9';\n",
+        );
+
+        let found = scan.find(src.as_bytes());
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            &src[found[0].clone()],
+            "dGhpcyBpcyBhIHNlY3JldCB0b2tlbiBrZXkgMTIzNDU2Nzg5MA=="
+        );
+
+        let redacted = String::from_utf8(redact(src.as_bytes(), &found)).unwrap();
+        assert!(redacted.starts_with("let token = \"[REDACTED]\";\n"));
+        assert!(redacted.contains("perfectly normal sentence"));
+    }
+}
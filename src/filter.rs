@@ -1,79 +1,188 @@
+use std::collections::HashMap;
 use std::path::Path;
 use walkdir::DirEntry;
 
 pub(crate) const PRUNED_DIRS: [&str; 3] = [".git", "target", "node_modules"];
-pub(crate) const EXCLUDED_FILENAMES: [&str; 4] =
-    ["LICENSE", "Makefile", "Cargo.lock", "dumpo.toml"];
+pub(crate) const EXCLUDED_FILENAMES: [&str; 9] = [
+    "LICENSE",
+    "Makefile",
+    "Cargo.lock",
+    "dumpo.toml",
+    "dumpo.yaml",
+    "dumpo.yml",
+    "dumpo.json",
+    ".dumpo-order",
+    ".dumpoinclude",
+];
+
+// Subset of EXCLUDED_FILENAMES that --include-lockfiles lets back in.
+pub(crate) const LOCKFILE_NAMES: [&str; 1] = ["Cargo.lock"];
+
+// Subset of EXCLUDED_FILENAMES that --include-meta lets back in.
+pub(crate) const META_NAMES: [&str; 2] = ["LICENSE", "Makefile"];
+
+pub(crate) fn excluded_filenames(include_lockfiles: bool, include_meta: bool) -> Vec<&'static str> {
+    EXCLUDED_FILENAMES
+        .into_iter()
+        .filter(|name| !(include_lockfiles && LOCKFILE_NAMES.contains(name)))
+        .filter(|name| !(include_meta && META_NAMES.contains(name)))
+        .collect()
+}
 
 pub(crate) const SECRET_FILENAMES: [&str; 1] = [".env"];
 pub(crate) const SECRET_PREFIXES: [&str; 1] = [".env."];
 pub(crate) const SECRET_EXTS: [&str; 4] = ["pem", "key", "p12", "pfx"];
 
+// Effective secret lists: the built-ins above plus extras from config. Extras can only
+// add to the built-ins, never remove them.
+#[derive(Debug, Clone)]
+pub(crate) struct SecretRules {
+    filenames: Vec<String>,
+    prefixes: Vec<String>,
+    exts: Vec<String>,
+}
+
+impl SecretRules {
+    pub(crate) fn with_extra(
+        filenames: Vec<String>,
+        prefixes: Vec<String>,
+        exts: Vec<String>,
+    ) -> Self {
+        fn extend(builtin: &[&str], extra: Vec<String>) -> Vec<String> {
+            builtin.iter().map(|s| s.to_string()).chain(extra).collect()
+        }
+
+        Self {
+            filenames: extend(&SECRET_FILENAMES, filenames),
+            prefixes: extend(&SECRET_PREFIXES, prefixes),
+            // Accept ".pem" as well as "pem", like max_file_bytes_by_ext keys.
+            exts: extend(
+                &SECRET_EXTS,
+                exts.into_iter()
+                    .map(|e| e.trim_start_matches('.').to_string())
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl Default for SecretRules {
+    fn default() -> Self {
+        Self::with_extra(Vec::new(), Vec::new(), Vec::new())
+    }
+}
+
 pub(crate) const EXCLUDED_EXTS: [&str; 24] = [
     "png", "jpg", "jpeg", "gif", "webp", "pdf", "zip", "gz", "bz2", "xz", "7z", "woff", "woff2",
     "ttf", "otf", "mp4", "mov", "mp3", "wav", "bin", "exe", "dll", "so", "dylib",
 ];
 
 pub(crate) fn should_prune_walk_entry(e: &DirEntry, include_hidden: bool) -> bool {
-    let name = e.file_name().to_string_lossy();
+    should_prune_entry(
+        &e.file_name().to_string_lossy(),
+        e.file_type().is_dir(),
+        include_hidden,
+    )
+}
 
-    if e.file_type().is_dir() && PRUNED_DIRS.iter().any(|d| name == *d) {
+// Shared by the walkdir and gitignore-aware walkers.
+pub(crate) fn should_prune_entry(name: &str, is_dir: bool, include_hidden: bool) -> bool {
+    if is_dir && PRUNED_DIRS.contains(&name) {
         return true;
     }
 
-    if !include_hidden && is_hidden(&name) {
+    if !include_hidden && is_hidden(name) {
         return true;
     }
 
     false
 }
 
-pub(crate) fn should_skip_file(path: &Path, include_hidden: bool) -> bool {
-    let name = match path.file_name().and_then(|s| s.to_str()) {
-        Some(n) => n,
-        None => return true,
+// Why should_skip_file left a file out; checked in this order, first match wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SkipReason {
+    // Secret file name or name prefix (.env, .env.local, ...).
+    Secret,
+    // Listed in the excluded names (LICENSE, Cargo.lock, dumpo.toml, ...), or no name at all.
+    ExcludedName,
+    Hidden,
+    // Secret extension (pem, key, ...); ahead of ExcludedExt, which --force-include can waive.
+    SecretExt,
+    // Binary-ish extension (png, zip, ...).
+    ExcludedExt,
+}
+
+pub(crate) fn should_skip_file(
+    path: &Path,
+    include_hidden: bool,
+    excluded_names: &[&str],
+    secrets: &SecretRules,
+) -> Option<SkipReason> {
+    // Non-UTF-8 names are checked lossily; dropping them is up to --skip-non-utf8-paths.
+    let name = match path.file_name() {
+        Some(n) => n.to_string_lossy(),
+        None => return Some(SkipReason::ExcludedName),
     };
+    let name = name.as_ref();
 
-    if is_secret_name(name) {
-        return true;
+    if is_secret_name(name, &secrets.filenames, &secrets.prefixes) {
+        return Some(SkipReason::Secret);
     }
 
-    if EXCLUDED_FILENAMES.contains(&name) {
-        return true;
+    if excluded_names.contains(&name) {
+        return Some(SkipReason::ExcludedName);
     }
 
     if !include_hidden && is_hidden(name) {
-        return true;
+        return Some(SkipReason::Hidden);
     }
 
-    if has_extension_in(path, &EXCLUDED_EXTS) {
-        return true;
+    if has_extension_in(path, &secrets.exts) {
+        return Some(SkipReason::SecretExt);
     }
 
-    if has_extension_in(path, &SECRET_EXTS) {
-        return true;
+    if has_extension_in(path, &EXCLUDED_EXTS) {
+        return Some(SkipReason::ExcludedExt);
     }
 
-    false
+    None
 }
 
 fn is_hidden(name: &str) -> bool {
     name.starts_with('.') && name != "."
 }
 
-fn is_secret_name(name: &str) -> bool {
-    if SECRET_FILENAMES.contains(&name) {
+// True if the file or any directory above it (within `rel`) is hidden.
+pub(crate) fn has_hidden_component(rel: &Path) -> bool {
+    rel.components()
+        .any(|c| is_hidden(&c.as_os_str().to_string_lossy()))
+}
+
+fn is_secret_name(name: &str, filenames: &[String], prefixes: &[String]) -> bool {
+    if filenames.iter().any(|f| f == name) {
         return true;
     }
-    SECRET_PREFIXES.iter().any(|p| name.starts_with(*p))
+    prefixes.iter().any(|p| name.starts_with(p.as_str()))
 }
 
-fn has_extension_in(path: &Path, exts: &[&str]) -> bool {
+fn has_extension_in(path: &Path, exts: &[impl AsRef<str>]) -> bool {
     let ext = match path.extension().and_then(|e| e.to_str()) {
         Some(e) => e,
         None => return false,
     };
-    exts.iter().any(|x| ext.eq_ignore_ascii_case(x))
+    exts.iter().any(|x| ext.eq_ignore_ascii_case(x.as_ref()))
+}
+
+// Same matching as `has_extension_in`; keys may be written with or without a leading dot.
+pub(crate) fn lookup_by_extension<'a, T>(
+    path: &Path,
+    by_ext: &'a HashMap<String, T>,
+) -> Option<&'a T> {
+    let ext = path.extension().and_then(|e| e.to_str())?;
+    by_ext
+        .iter()
+        .find(|(k, _)| ext.eq_ignore_ascii_case(k.trim_start_matches('.')))
+        .map(|(_, v)| v)
 }
 
 #[cfg(test)]
@@ -87,10 +196,34 @@ mod tests {
         repo.write(".env", "SECRET=1");
         repo.write(".env.local", "SECRET=2");
 
-        assert!(should_skip_file(&repo.path().join(".env"), true));
-        assert!(should_skip_file(&repo.path().join(".env.local"), true));
-        assert!(should_skip_file(&repo.path().join(".env"), false));
-        assert!(should_skip_file(&repo.path().join(".env.local"), false));
+        assert!(should_skip_file(
+            &repo.path().join(".env"),
+            true,
+            &EXCLUDED_FILENAMES,
+            &SecretRules::default()
+        )
+        .is_some());
+        assert!(should_skip_file(
+            &repo.path().join(".env.local"),
+            true,
+            &EXCLUDED_FILENAMES,
+            &SecretRules::default()
+        )
+        .is_some());
+        assert!(should_skip_file(
+            &repo.path().join(".env"),
+            false,
+            &EXCLUDED_FILENAMES,
+            &SecretRules::default()
+        )
+        .is_some());
+        assert!(should_skip_file(
+            &repo.path().join(".env.local"),
+            false,
+            &EXCLUDED_FILENAMES,
+            &SecretRules::default()
+        )
+        .is_some());
     }
 
     #[test]
@@ -98,15 +231,69 @@ mod tests {
         let repo = TempRepo::new();
         repo.write(".hidden.txt", "ok");
 
-        assert!(should_skip_file(&repo.path().join(".hidden.txt"), false));
-        assert!(!should_skip_file(&repo.path().join(".hidden.txt"), true));
+        assert!(should_skip_file(
+            &repo.path().join(".hidden.txt"),
+            false,
+            &EXCLUDED_FILENAMES,
+            &SecretRules::default()
+        )
+        .is_some());
+        assert!(should_skip_file(
+            &repo.path().join(".hidden.txt"),
+            true,
+            &EXCLUDED_FILENAMES,
+            &SecretRules::default()
+        )
+        .is_none());
     }
 
     #[test]
     fn should_skip_file_excludes_lockfile() {
         let repo = TempRepo::new();
         repo.write("Cargo.lock", "lock");
-        assert!(should_skip_file(&repo.path().join("Cargo.lock"), true));
+        assert!(should_skip_file(
+            &repo.path().join("Cargo.lock"),
+            true,
+            &EXCLUDED_FILENAMES,
+            &SecretRules::default()
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn include_lockfiles_drops_only_lockfiles_from_exclusions() {
+        let repo = TempRepo::new();
+        repo.write("Cargo.lock", "lock");
+        repo.write("LICENSE", "mit");
+
+        let names = excluded_filenames(true, false);
+        assert!(should_skip_file(
+            &repo.path().join("Cargo.lock"),
+            true,
+            &names,
+            &SecretRules::default()
+        )
+        .is_none());
+        assert!(should_skip_file(
+            &repo.path().join("LICENSE"),
+            true,
+            &names,
+            &SecretRules::default()
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn include_meta_drops_only_meta_files_from_exclusions() {
+        let names = excluded_filenames(false, true);
+        let skip = |name: &str| {
+            should_skip_file(Path::new(name), true, &names, &SecretRules::default()).is_some()
+        };
+
+        assert!(!skip("Makefile"));
+        assert!(!skip("LICENSE"));
+        assert!(skip("Cargo.lock"));
+        assert!(skip("dumpo.toml"));
     }
 
     #[test]
@@ -115,8 +302,20 @@ mod tests {
         repo.write("a.PNG", "x");
         repo.write("b.PdF", "x");
 
-        assert!(should_skip_file(&repo.path().join("a.PNG"), true));
-        assert!(should_skip_file(&repo.path().join("b.PdF"), true));
+        assert!(should_skip_file(
+            &repo.path().join("a.PNG"),
+            true,
+            &EXCLUDED_FILENAMES,
+            &SecretRules::default()
+        )
+        .is_some());
+        assert!(should_skip_file(
+            &repo.path().join("b.PdF"),
+            true,
+            &EXCLUDED_FILENAMES,
+            &SecretRules::default()
+        )
+        .is_some());
     }
 
     #[test]
@@ -125,9 +324,90 @@ mod tests {
         repo.write("LICENSE", "mit");
         repo.write("Makefile", "all:\n\techo hi\n");
 
-        assert!(should_skip_file(&repo.path().join("LICENSE"), true));
-        assert!(should_skip_file(&repo.path().join("Makefile"), true));
-        assert!(should_skip_file(&repo.path().join("LICENSE"), false));
-        assert!(should_skip_file(&repo.path().join("Makefile"), false));
+        assert!(should_skip_file(
+            &repo.path().join("LICENSE"),
+            true,
+            &EXCLUDED_FILENAMES,
+            &SecretRules::default()
+        )
+        .is_some());
+        assert!(should_skip_file(
+            &repo.path().join("Makefile"),
+            true,
+            &EXCLUDED_FILENAMES,
+            &SecretRules::default()
+        )
+        .is_some());
+        assert!(should_skip_file(
+            &repo.path().join("LICENSE"),
+            false,
+            &EXCLUDED_FILENAMES,
+            &SecretRules::default()
+        )
+        .is_some());
+        assert!(should_skip_file(
+            &repo.path().join("Makefile"),
+            false,
+            &EXCLUDED_FILENAMES,
+            &SecretRules::default()
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn configured_secret_names_add_to_builtins() {
+        let repo = TempRepo::new();
+        repo.write("secrets.yaml", "token: x");
+        repo.write("config.yaml", "ok: true");
+        repo.write("creds.gpg", "x");
+
+        let secrets = SecretRules::with_extra(
+            vec!["secrets.yaml".to_string()],
+            Vec::new(),
+            vec![".gpg".to_string()],
+        );
+        let skip =
+            |name: &str| should_skip_file(&repo.path().join(name), true, &[], &secrets).is_some();
+
+        assert!(skip("secrets.yaml"));
+        assert!(skip("creds.gpg"));
+        assert!(!skip("config.yaml"));
+        // Built-ins still apply alongside the extras.
+        assert!(skip(".env"));
+        assert!(skip("server.pem"));
+    }
+
+    #[test]
+    fn has_hidden_component_checks_every_directory() {
+        assert!(has_hidden_component(Path::new(".github/workflows/ci.yml")));
+        assert!(has_hidden_component(Path::new("src/.env.example")));
+        assert!(!has_hidden_component(Path::new("src/lib.rs")));
+    }
+
+    #[test]
+    fn should_skip_file_reports_each_reason() {
+        let secrets = SecretRules::default();
+        let reason = |name: &str, include_hidden| {
+            should_skip_file(
+                Path::new(name),
+                include_hidden,
+                &EXCLUDED_FILENAMES,
+                &secrets,
+            )
+        };
+
+        assert_eq!(reason(".env.local", true), Some(SkipReason::Secret));
+        assert_eq!(reason("LICENSE", true), Some(SkipReason::ExcludedName));
+        assert_eq!(reason("..", true), Some(SkipReason::ExcludedName));
+        assert_eq!(reason(".eslintrc", false), Some(SkipReason::Hidden));
+        assert_eq!(reason("logo.PNG", true), Some(SkipReason::ExcludedExt));
+        assert_eq!(reason("server.pem", true), Some(SkipReason::SecretExt));
+        let bin_secret = SecretRules::with_extra(Vec::new(), Vec::new(), vec!["bin".to_string()]);
+        assert_eq!(
+            should_skip_file(Path::new("k.bin"), true, &[], &bin_secret),
+            Some(SkipReason::SecretExt)
+        );
+        assert_eq!(reason(".eslintrc", true), None);
+        assert_eq!(reason("src/lib.rs", false), None);
     }
 }
@@ -1,24 +1,239 @@
-use std::path::Path;
+use crate::timestamp;
+use anyhow::Context;
+use globset::{Glob, GlobMatcher};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// `schema_version` of the JSON dump and the manifest; bump it on any breaking change to either.
+pub(crate) const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum OutputFormat {
+    #[default]
+    Markdown,
+    Json,
+    // Markdown with each file in a collapsible <details> block (for GitHub issues).
+    #[serde(rename = "md-details")]
+    MdDetails,
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Markdown => write!(f, "markdown"),
+            OutputFormat::Json => write!(f, "json"),
+            OutputFormat::MdDetails => write!(f, "md-details"),
+        }
+    }
+}
+
+// Character the code fences around file contents are made of.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum FenceChar {
+    #[default]
+    Backtick,
+    Tilde,
+}
+
+impl FenceChar {
+    pub(crate) fn fence(self) -> &'static str {
+        match self {
+            FenceChar::Backtick => "```",
+            FenceChar::Tilde => "~~~",
+        }
+    }
+
+    // Closing fence plus the blank line that separates files.
+    pub(crate) fn close(self) -> &'static str {
+        match self {
+            FenceChar::Backtick => "```\n\n",
+            FenceChar::Tilde => "~~~\n\n",
+        }
+    }
+}
+
+// How --toc lists the included files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum TocStyle {
+    // Full relative paths, in dump order.
+    #[default]
+    Paths,
+    // Basenames indented under their directories, sorted.
+    Names,
+}
 
 pub(crate) const DUMP_TITLE: &str = "# dumpo pack";
+pub(crate) const DEFAULT_FILE_HEADING_PREFIX: &str = "## ";
+pub(crate) const TOC_HEADING: &str = "## Contents";
+
+pub(crate) const DEFAULT_TRUNCATION_FOOTER: &str = "... (truncated: max_total_bytes reached)";
+pub(crate) const DEFAULT_FILE_TRUNCATED_MARKER: &str = "(file truncated)";
+pub(crate) const LINE_TRUNCATED_MARKER: &str = "… (line truncated)";
+pub(crate) const EMPTY_FILE_NOTE: &str = "(empty file)";
+pub(crate) const BINARY_LISTING_HEADING: &str = "## Binary files (not shown)";
+
+pub(crate) fn front_matter(file_count: usize, generated_at: SystemTime) -> String {
+    format!(
+        "---\ntool: dumpo\nversion: {}\nfile_count: {file_count}\ngenerated_at: {}\n---\n",
+        env!("CARGO_PKG_VERSION"),
+        timestamp::rfc3339_utc(generated_at)
+    )
+}
+
+pub(crate) fn truncation_footer(text: &str) -> String {
+    format!("\n{text}\n")
+}
+
+pub(crate) fn max_files_footer(max_files: usize) -> String {
+    truncation_footer(&format!("... (truncated: max_files = {max_files} reached)"))
+}
 
-pub(crate) const CODEBLOCK_CLOSE: &str = "```\n\n";
-pub(crate) const TRUNCATION_FOOTER: &str = "\n... (truncated: max_total_bytes reached)\n";
-pub(crate) const FILE_TRUNCATED_MARKER: &str = "(file truncated)\n\n";
+pub(crate) fn file_truncated_marker(text: &str) -> String {
+    format!("{text}\n\n")
+}
 
 pub(crate) fn root_line(root: &Path) -> String {
     format!("- root: {}", root.display())
 }
 
-pub(crate) fn file_heading(rel: &Path) -> String {
-    format!("## {}", rel.display())
+pub(crate) fn total_lines_line(total: usize) -> String {
+    format!("- total_lines: {total}")
+}
+
+pub(crate) fn generated_line(at: SystemTime) -> String {
+    format!("- generated: {}", timestamp::rfc3339_utc(at))
 }
 
-pub(crate) fn code_fence_open(path: &Path) -> String {
-    format!("```{}", language_hint(path))
+pub(crate) fn file_heading(prefix: &str, rel: &Path) -> String {
+    format!("{prefix}{}", rel.display())
+}
+
+// Section heading for --group-by-dir; files at the root go under ROOT_GROUP.
+pub(crate) const ROOT_GROUP: &str = "(root)";
+
+pub(crate) fn group_heading(top_level_dir: Option<&str>) -> String {
+    match top_level_dir {
+        Some(dir) => format!("# {dir}/"),
+        None => format!("# {ROOT_GROUP}"),
+    }
+}
+
+pub(crate) const DETAILS_CLOSE: &str = "</details>\n\n";
+
+pub(crate) fn details_open(summary: &str) -> String {
+    let summary = summary
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    format!("<details><summary>{summary}</summary>")
+}
+
+pub(crate) fn symlink_note(prefix: &str, rel: &Path, target: &Path) -> String {
+    format!("{prefix}{} -> {}", rel.display(), target.display())
+}
+
+// `unpack` finds files by their heading, so the prefix must be a visible single-line
+// marker that can't be mistaken for a code fence.
+pub(crate) fn validate_file_heading_prefix(prefix: &str) -> anyhow::Result<()> {
+    if prefix.trim().is_empty() {
+        anyhow::bail!("file_heading_prefix must not be empty");
+    }
+    if prefix.contains(['\n', '\r']) {
+        anyhow::bail!("file_heading_prefix must be a single line: {prefix:?}");
+    }
+    if prefix.starts_with(['`', '~']) {
+        anyhow::bail!("file_heading_prefix must not look like a code fence: {prefix:?}");
+    }
+    Ok(())
+}
+
+pub(crate) fn toc_lines(paths: &[PathBuf], style: TocStyle) -> Vec<String> {
+    if style == TocStyle::Paths {
+        return paths.iter().map(|p| format!("- {}", p.display())).collect();
+    }
+
+    let mut sorted: Vec<&Path> = paths.iter().map(PathBuf::as_path).collect();
+    sorted.sort();
+
+    let mut lines = Vec::new();
+    let mut prev_dirs: Vec<&OsStr> = Vec::new();
+    for path in sorted {
+        let dirs: Vec<&OsStr> = path
+            .parent()
+            .map(|d| d.iter().collect())
+            .unwrap_or_default();
+        let shared = prev_dirs
+            .iter()
+            .zip(&dirs)
+            .take_while(|(a, b)| a == b)
+            .count();
+        for (depth, dir) in dirs.iter().enumerate().skip(shared) {
+            lines.push(format!(
+                "{}- {}/",
+                "  ".repeat(depth),
+                dir.to_string_lossy()
+            ));
+        }
+        let name = path.file_name().unwrap_or(path.as_os_str());
+        lines.push(format!(
+            "{}- {}",
+            "  ".repeat(dirs.len()),
+            name.to_string_lossy()
+        ));
+        prev_dirs = dirs;
+    }
+    lines
+}
+
+pub(crate) fn binary_listing_line(rel: &Path, size: &str) -> String {
+    format!("- {} ({size})", rel.display())
+}
+
+pub(crate) fn code_fence_open(
+    path: &Path,
+    fence: FenceChar,
+    overrides: &LanguageOverrides,
+) -> String {
+    format!("{}{}", fence.fence(), overrides.language_for(path))
+}
+
+// The `[language_overrides]` config table: glob -> fence language, checked in file order
+// before the built-in extension mapping. Patterns match at any depth (`*.conf`,
+// `deploy/.env.example`).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LanguageOverrides(Vec<(GlobMatcher, String)>);
+
+impl LanguageOverrides {
+    pub(crate) fn new(overrides: IndexMap<String, String>) -> anyhow::Result<Self> {
+        let mut compiled = Vec::with_capacity(overrides.len());
+        for (pattern, language) in overrides {
+            let anchored = if pattern.starts_with("**/") {
+                pattern.clone()
+            } else {
+                format!("**/{pattern}")
+            };
+            let glob = Glob::new(&anchored).with_context(|| {
+                format!("language_overrides: invalid glob pattern: {pattern:?}")
+            })?;
+            compiled.push((glob.compile_matcher(), language));
+        }
+        Ok(Self(compiled))
+    }
+
+    pub(crate) fn language_for(&self, path: &Path) -> &str {
+        self.0
+            .iter()
+            .find(|(glob, _)| glob.is_match(path))
+            .map_or_else(|| language_hint(path), |(_, language)| language.as_str())
+    }
 }
 
-fn language_hint(path: &Path) -> &'static str {
+pub(crate) fn language_hint(path: &Path) -> &'static str {
     match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
         "rs" => "rust",
         "toml" => "toml",
@@ -0,0 +1,29 @@
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Command;
+
+// Paths (relative to `root`, '/'-separated) that `git diff --name-only <reference>` reports,
+// i.e. changed in the working tree since that commit. Deleted files are listed too; they
+// simply never turn up in the walk.
+pub(crate) fn changed_since(root: &Path, reference: &str) -> Result<HashSet<String>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["diff", "--name-only", "-z", "--relative", reference, "--"])
+        .output()
+        .context("--since: failed to run git")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "--since {reference}: git diff failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(output
+        .stdout
+        .split(|&b| b == 0)
+        .filter(|p| !p.is_empty())
+        .map(|p| String::from_utf8_lossy(p).into_owned())
+        .collect())
+}
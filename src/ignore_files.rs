@@ -0,0 +1,125 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::fs;
+use std::path::Path;
+
+// Builds a matcher from the root `.gitattributes` entries carrying `export-ignore`,
+// i.e. the paths `git archive` would leave out. Bad patterns are reported, not fatal.
+pub(crate) fn export_ignore_matcher(root: &Path, warnings: &mut Vec<String>) -> Option<Gitignore> {
+    let path = root.join(".gitattributes");
+    let contents = fs::read_to_string(&path).ok()?;
+
+    let mut builder = GitignoreBuilder::new(root);
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        let Some(pattern) = parts.next() else {
+            continue;
+        };
+        if !parts.any(|attr| attr == "export-ignore") {
+            continue;
+        }
+
+        if let Err(e) = builder.add_line(Some(path.clone()), pattern) {
+            warnings.push(format!(
+                "{}: ignoring pattern {pattern:?}: {e}",
+                path.display()
+            ));
+        }
+    }
+
+    match builder.build() {
+        Ok(gi) => Some(gi),
+        Err(e) => {
+            warnings.push(format!("{}: {e}", path.display()));
+            None
+        }
+    }
+}
+
+// Builds a matcher from the root `.dockerignore`. Docker resolves every pattern
+// against the build context root, so patterns are anchored there (unlike
+// .gitignore, where `tmp/` would match at any depth); `**` and `!` still work.
+pub(crate) fn dockerignore_matcher(root: &Path, warnings: &mut Vec<String>) -> Option<Gitignore> {
+    let path = root.join(".dockerignore");
+    let contents = fs::read_to_string(&path).ok()?;
+
+    let mut builder = GitignoreBuilder::new(root);
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (negate, pattern) = match line.strip_prefix('!') {
+            Some(rest) => ("!", rest),
+            None => ("", line),
+        };
+        let pattern = pattern.trim_start_matches("./").trim_start_matches('/');
+        let anchored = format!("{negate}/{pattern}");
+
+        if let Err(e) = builder.add_line(Some(path.clone()), &anchored) {
+            warnings.push(format!(
+                "{}: ignoring pattern {line:?}: {e}",
+                path.display()
+            ));
+        }
+    }
+
+    match builder.build() {
+        Ok(gi) => Some(gi),
+        Err(e) => {
+            warnings.push(format!("{}: {e}", path.display()));
+            None
+        }
+    }
+}
+
+pub(crate) fn is_ignored(matcher: &Gitignore, rel: &Path) -> bool {
+    matcher.matched_path_or_any_parents(rel, false).is_ignore()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempRepo;
+
+    #[test]
+    fn export_ignore_matcher_only_picks_export_ignore_entries() {
+        let repo = TempRepo::new();
+        repo.write(
+            ".gitattributes",
+            "# vendored code\nvendor/ export-ignore\n*.sh text eol=lf\ndocs/** -export-ignore\n",
+        );
+
+        let mut warnings = Vec::new();
+        let m = export_ignore_matcher(repo.path(), &mut warnings).unwrap();
+
+        assert!(is_ignored(&m, Path::new("vendor/lib/a.rs")));
+        assert!(!is_ignored(&m, Path::new("scripts/run.sh")));
+        assert!(!is_ignored(&m, Path::new("docs/guide.md")));
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn export_ignore_matcher_is_none_without_gitattributes() {
+        let repo = TempRepo::new();
+        assert!(export_ignore_matcher(repo.path(), &mut Vec::new()).is_none());
+    }
+
+    #[test]
+    fn dockerignore_matcher_anchors_patterns_at_root() {
+        let repo = TempRepo::new();
+        repo.write(".dockerignore", "# scratch\ntmp/\n**/*.log\n!keep.log\n");
+
+        let m = dockerignore_matcher(repo.path(), &mut Vec::new()).unwrap();
+
+        assert!(is_ignored(&m, Path::new("tmp/a.txt")));
+        assert!(!is_ignored(&m, Path::new("src/tmp/a.txt")));
+        assert!(is_ignored(&m, Path::new("src/debug.log")));
+        assert!(!is_ignored(&m, Path::new("keep.log")));
+    }
+}
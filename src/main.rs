@@ -1,26 +1,46 @@
 use anyhow::Result;
 use clap::{Args, Parser, Subcommand};
 use std::path::PathBuf;
+use std::time::Duration;
 
+mod archive;
+mod cache;
+mod check;
 mod clipboard;
+mod completions;
 mod config;
 mod dump;
+mod duration;
+mod entropy;
 mod filter;
 mod format;
+mod git;
+mod ignore_files;
 mod pack;
+mod pick;
 mod selector;
+mod style;
+mod timestamp;
+mod tokens;
+mod units;
+mod unpack;
+mod version;
 
 #[cfg(test)]
 mod testutil;
 
 #[derive(Parser)]
-#[command(name = "dumpo", about = "Dump a repo into a paste-ready LLM prompt")]
+#[command(
+    name = "dumpo",
+    version,
+    about = "Dump a repo into a paste-ready LLM prompt"
+)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
 }
 
-#[derive(Args, Debug, Clone)]
+#[derive(Args, Debug, Clone, Default)]
 pub(crate) struct PackArgs {
     #[arg(default_value = ".")]
     pub(crate) path: PathBuf,
@@ -32,47 +52,452 @@ pub(crate) struct PackArgs {
     #[arg(long)]
     pub(crate) max_total_bytes: Option<usize>,
 
+    // Skip (without reading) any file larger than this, to bound memory on huge files.
+    #[arg(long)]
+    pub(crate) max_read_bytes: Option<u64>,
+
+    // Truncate probable fixtures (large files under fixtures/, testdata/, ...) to a small cap.
+    #[arg(long, default_value_t = false)]
+    pub(crate) trim_fixtures: bool,
+
+    // Cap for --trim-fixtures (default 2000); files under it aren't treated as fixtures.
+    #[arg(long)]
+    pub(crate) fixture_max_bytes: Option<usize>,
+
+    // Dump only the N largest selected files (ties broken by path), e.g. to find bloat.
+    #[arg(long, value_name = "N")]
+    pub(crate) top_largest: Option<usize>,
+
+    // Cap on the number of selected files; what happens beyond it is set by --max-files-mode.
+    #[arg(long)]
+    pub(crate) max_files: Option<usize>,
+
+    #[arg(long, value_enum)]
+    pub(crate) max_files_mode: Option<dump::MaxFilesMode>,
+
+    // At max_total_bytes, stop at the file that doesn't fit (default) or skip it and go on.
+    #[arg(long, value_enum)]
+    pub(crate) total_limit_mode: Option<dump::TotalLimitMode>,
+
+    // Skip files whose decoded text is more than this fraction U+FFFD (0.0-1.0).
+    #[arg(long)]
+    pub(crate) max_replacement_ratio: Option<f64>,
+
     // When present, sets true. Absence means “use config/default”.
-    #[arg(long, action = clap::ArgAction::SetTrue)]
+    #[arg(long, num_args = 0, default_missing_value = "true")]
     pub(crate) include_hidden: Option<bool>,
 
     // When present, sets false. Absence means “use config/default”.
-    #[arg(long = "no-include-hidden", action = clap::ArgAction::SetFalse)]
+    #[arg(
+        long = "no-include-hidden",
+        num_args = 0,
+        default_missing_value = "false"
+    )]
     pub(crate) no_include_hidden: Option<bool>,
 
+    // Append each file's size to its heading, e.g. `## src/lib.rs (1.2 KB)`.
+    #[arg(long, default_value_t = false)]
+    pub(crate) show_sizes: bool,
+
+    // Append each file's permission bits to its heading, e.g. `## run.sh (0755)`. Unix only.
+    #[arg(long, default_value_t = false)]
+    pub(crate) show_mode: bool,
+
+    // Drop the blank lines after each heading and closing fence.
+    #[arg(long, default_value_t = false)]
+    pub(crate) compact: bool,
+
+    // Start each top-level directory's files with a `# dir/` section heading.
+    #[arg(long, default_value_t = false)]
+    pub(crate) group_by_dir: bool,
+
+    // Print sizes as exact byte counts instead of KB/MB (for machine-read output).
+    #[arg(long, default_value_t = false)]
+    pub(crate) exact_bytes: bool,
+
+    // Flag quoted high-entropy strings (likely hard-coded tokens); see --entropy-action.
+    #[arg(long, default_value_t = false)]
+    pub(crate) scan_entropy: bool,
+
+    // Redact flagged strings (default) or leave the whole file out.
+    #[arg(long, value_enum, requires = "scan_entropy")]
+    pub(crate) entropy_action: Option<entropy::EntropyAction>,
+
+    // Bits per character a string must exceed to be flagged (default 4.5).
+    #[arg(long, requires = "scan_entropy")]
+    pub(crate) entropy_threshold: Option<f64>,
+
+    // Minimum length of a flagged string (default 20).
+    #[arg(long, requires = "scan_entropy")]
+    pub(crate) entropy_min_len: Option<usize>,
+
+    // Most bytes any one top-level directory may contribute; later files from it are left out.
+    #[arg(long)]
+    pub(crate) per_dir_byte_cap: Option<usize>,
+
+    // Split max_total_bytes evenly across the top-level directories that have matches.
+    #[arg(long, default_value_t = false)]
+    pub(crate) balance_dirs: bool,
+
+    // Prefix each line with its line number (markdown only).
+    #[arg(long, default_value_t = false)]
+    pub(crate) line_numbers: bool,
+
+    // Cut lines longer than this many bytes, marking each cut with "… (line truncated)".
+    #[arg(long)]
+    pub(crate) max_line_bytes: Option<usize>,
+
+    // After the dump, print file count and bytes per language to stderr.
+    #[arg(long, default_value_t = false)]
+    pub(crate) lang_summary: bool,
+
+    // After the dump, print estimated tokens per language to stderr, largest first.
+    #[arg(long, default_value_t = false)]
+    pub(crate) token_summary: bool,
+
+    // Dump stdin (read to EOF) as a single file instead of walking PATH.
+    #[arg(long, default_value_t = false)]
+    pub(crate) stdin: bool,
+
+    // Heading (and language hint, via its extension) for --stdin content.
+    #[arg(long, default_value = "stdin", requires = "stdin")]
+    pub(crate) stdin_name: String,
+
+    // Note symlinks as `## path -> target` instead of dumping what they point to.
+    #[arg(long, default_value_t = false)]
+    pub(crate) note_symlinks: bool,
+
+    // Re-admit files under the root .git matching these globs (e.g. ".git/config").
+    #[arg(long, action = clap::ArgAction::Append)]
+    pub(crate) include_git_files: Vec<String>,
+
+    // Dump files matching these globs even if their extension or content looks binary.
+    // --include/--exclude still apply, and secrets are still never dumped.
+    #[arg(long, action = clap::ArgAction::Append)]
+    pub(crate) force_include: Vec<String>,
+
+    // Dump lockfiles such as Cargo.lock, which are skipped by default.
+    #[arg(long, default_value_t = false)]
+    pub(crate) include_lockfiles: bool,
+
+    // Dump LICENSE and Makefile, which are skipped by default.
+    #[arg(long, default_value_t = false)]
+    pub(crate) include_meta: bool,
+
+    // List every file the secret filter kept out (paths only) on stderr, hidden ones included.
+    #[arg(long, default_value_t = false)]
+    pub(crate) plain_secrets_warning: bool,
+
     #[arg(long, action = clap::ArgAction::Append)]
     pub(crate) include: Vec<String>,
 
     #[arg(long, action = clap::ArgAction::Append)]
     pub(crate) exclude: Vec<String>,
 
+    // Files of include/exclude globs (one per line, `#` comments), added to the ones above.
+    #[arg(long)]
+    pub(crate) include_from: Option<PathBuf>,
+
+    #[arg(long)]
+    pub(crate) exclude_from: Option<PathBuf>,
+
+    // File order: path (default), size or mtime, ascending; ties fall back to the path.
+    #[arg(long, value_enum, default_value_t = dump::SortOrder::Path)]
+    pub(crate) sort: dump::SortOrder,
+
+    // Dump files in --include argument order (by first matching pattern); --sort breaks ties.
+    #[arg(long, default_value_t = false)]
+    pub(crate) ordered_includes: bool,
+
+    // Set by `pick`, whose includes already name exactly the files to dump.
+    #[arg(skip)]
+    pub(crate) skip_include_file: bool,
+
+    // Leave out these exact repo-relative paths (no glob matching).
+    #[arg(long, action = clap::ArgAction::Append)]
+    pub(crate) exclude_path: Vec<String>,
+
+    // Walk the tree with N threads (helps on network filesystems); output is unchanged.
+    #[arg(long)]
+    pub(crate) walk_threads: Option<usize>,
+
+    // Skip paths matched by the root .dockerignore.
+    #[arg(long, default_value_t = false)]
+    pub(crate) respect_dockerignore: bool,
+
+    // Skip paths matched by .gitignore files (applied per directory like git does) and .git/info/exclude.
+    #[arg(long, default_value_t = false)]
+    pub(crate) respect_gitignore: bool,
+
+    // Skip paths marked `export-ignore` in the root .gitattributes (like `git archive`).
+    #[arg(long, default_value_t = false)]
+    pub(crate) respect_gitattributes: bool,
+
+    // Only dump files modified within this window, e.g. "24h" or "7d".
+    #[arg(long, value_parser = duration::parse_duration)]
+    pub(crate) modified_within: Option<Duration>,
+
+    // Only dump files `git diff --name-only <REF>` lists (changed since that commit).
+    #[arg(long, value_name = "REF")]
+    pub(crate) since: Option<String>,
+
+    // Skip files with a generated-code marker (e.g. `@generated`) in their first lines.
+    #[arg(long, default_value_t = false)]
+    pub(crate) skip_generated: bool,
+
+    // Dump git LFS pointer stubs, which are skipped by default.
+    #[arg(long, default_value_t = false)]
+    pub(crate) include_lfs_pointers: bool,
+
+    // Drop files whose path isn't valid UTF-8 (by default they're kept, shown with U+FFFD).
+    #[arg(long, default_value_t = false)]
+    pub(crate) skip_non_utf8_paths: bool,
+
+    // Keep only files whose fence language (rust, python, markdown, ...) is one of these.
+    #[arg(long = "lang", action = clap::ArgAction::Append)]
+    pub(crate) languages: Vec<String>,
+
+    // Only dump files whose content matches this regex.
+    #[arg(long)]
+    pub(crate) grep: Option<String>,
+
+    // Text appended when max_total_bytes cuts the dump short.
+    #[arg(long)]
+    pub(crate) truncation_footer: Option<String>,
+
+    // Text emitted after a file cut short by max_file_bytes.
+    #[arg(long)]
+    pub(crate) file_truncated_marker: Option<String>,
+
+    // Prefix prepended to every file heading (display only; globs still match the bare path).
+    #[arg(long)]
+    pub(crate) path_prefix: Option<String>,
+
+    // Text shown in the `- root:` line (and JSON `root`) instead of the root path.
+    #[arg(long, conflicts_with = "anonymize_paths")]
+    pub(crate) root_label: Option<String>,
+
+    // Replace directory names in headings with stable placeholders (dir1, dir2, ...).
+    #[arg(long, default_value_t = false)]
+    pub(crate) anonymize_paths: bool,
+
+    // Output format; falls back to config, then markdown.
+    #[arg(long, value_enum)]
+    pub(crate) format: Option<format::OutputFormat>,
+
+    // Starts every file heading (default "## "); pass the same value to `unpack`.
+    #[arg(long)]
+    pub(crate) file_heading_prefix: Option<String>,
+
+    // End the dump with a "## Contents" list of the included files.
+    #[arg(long, default_value_t = false)]
+    pub(crate) toc: bool,
+
+    // How --toc renders paths: full relative paths (default) or indented basenames.
+    #[arg(long, value_enum, requires = "toc")]
+    pub(crate) toc_style: Option<format::TocStyle>,
+
+    // Break up ``` (or ~~~) runs inside files with a zero-width joiner so they can't end the
+    // outer fence. Visibly alters content: the joiners survive copy/paste.
+    #[arg(long, default_value_t = false)]
+    pub(crate) escape_fences: bool,
+
+    // Code fence character: backtick (```, default) or tilde (~~~).
+    #[arg(long, value_enum)]
+    pub(crate) fence_char: Option<format::FenceChar>,
+
+    // Append a section listing skipped binary files and their sizes.
+    #[arg(long, default_value_t = false)]
+    pub(crate) list_binaries: bool,
+
+    // Prepend a YAML front-matter block (tool, version, file_count, generated_at).
+    #[arg(long, default_value_t = false)]
+    pub(crate) front_matter: bool,
+
+    // Add a `- generated: <RFC 3339 UTC>` line under the root line.
+    #[arg(long, default_value_t = false)]
+    pub(crate) timestamp: bool,
+
+    // Add a `- total_lines: N` header line counting the lines of every included file.
+    #[arg(long, default_value_t = false, conflicts_with = "no_header")]
+    pub(crate) total_lines: bool,
+
+    // Leave out the `# dumpo pack` / `- root:` header; the dump starts at the first file.
+    #[arg(long, default_value_t = false, conflicts_with = "timestamp")]
+    pub(crate) no_header: bool,
+
     #[arg(long, default_value_t = false)]
     pub(crate) verbose: bool,
 
+    // Suppress all stderr output except errors.
+    #[arg(long, default_value_t = false, conflicts_with = "verbose")]
+    pub(crate) quiet: bool,
+
     // Optional explicit config path; if not set, search ancestors.
     #[arg(long)]
     pub(crate) config: Option<PathBuf>,
 
+    // Apply the config's [profiles.NAME] keys on top of the rest of the config.
+    #[arg(long, conflicts_with = "no_config")]
+    pub(crate) profile: Option<String>,
+
     // Disable config loading entirely.
     #[arg(long, default_value_t = false)]
     pub(crate) no_config: bool,
 
-    #[arg(long, default_value_t = !cfg!(target_os = "macos"))]
-    pub(crate) stdout: bool,
+    // Sinks: CLI flag > config > platform default (clipboard on macOS, stdout elsewhere).
+    #[arg(long, num_args = 0, default_missing_value = "true")]
+    pub(crate) stdout: Option<bool>,
+
+    #[arg(long = "no-stdout", num_args = 0, default_missing_value = "false")]
+    pub(crate) no_stdout: Option<bool>,
+
+    #[arg(long, num_args = 0, default_missing_value = "true")]
+    pub(crate) clipboard: Option<bool>,
+
+    #[arg(long = "no-clipboard", num_args = 0, default_missing_value = "false")]
+    pub(crate) no_clipboard: Option<bool>,
+
+    // If no clipboard backend exists, warn and write to stdout instead of failing.
+    #[arg(long, default_value_t = false)]
+    pub(crate) clipboard_fallback_stdout: bool,
+
+    // Kill the clipboard command if it hasn't finished after this long, e.g. "10s".
+    #[arg(long, value_parser = duration::parse_duration)]
+    pub(crate) clipboard_timeout: Option<Duration>,
+
+    // Exit with an error (and write nothing) when no file made it into the dump.
+    #[arg(long, default_value_t = false)]
+    pub(crate) error_on_empty: bool,
+
+    // Turn warnings about contradictory settings (e.g. max_file_bytes > max_total_bytes)
+    // into errors.
+    #[arg(long, default_value_t = false)]
+    pub(crate) strict: bool,
+
+    // Exit with an error if any file or the dump as a whole was truncated.
+    #[arg(long, default_value_t = false)]
+    pub(crate) fail_on_truncate: bool,
+
+    // Print the SHA-256 of the dump to stderr.
+    #[arg(long, default_value_t = false)]
+    pub(crate) print_hash: bool,
+
+    // Print only the SHA-256 of the dump to stdout; no sink receives the dump itself.
+    #[arg(long, default_value_t = false)]
+    pub(crate) hash_only: bool,
+
+    // Also write the dump to this file.
+    #[arg(long)]
+    pub(crate) output: Option<PathBuf>,
+
+    // Write a JSON manifest of the included files (path, bytes, truncated, language, sha256).
+    #[arg(long)]
+    pub(crate) manifest: Option<PathBuf>,
+
+    // Reuse the previous dump when no option, config or selected file (path, size, mtime) changed.
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with_all = [
+            "stdin",
+            "timestamp",
+            "front_matter",
+            "manifest",
+            "lang_summary",
+            "token_summary",
+        ]
+    )]
+    pub(crate) cache: bool,
+
+    // Where --cache keeps dumps (default: $XDG_CACHE_HOME/dumpo or ~/.cache/dumpo).
+    #[arg(long, requires = "cache")]
+    pub(crate) cache_dir: Option<PathBuf>,
+
+    // Append to --output (separated by a blank line) instead of truncating it.
+    #[arg(long, default_value_t = false, requires = "output")]
+    pub(crate) append: bool,
+}
+
+#[derive(Args, Debug, Clone, Default)]
+pub(crate) struct CheckArgs {
+    #[arg(default_value = ".")]
+    pub(crate) path: PathBuf,
+
+    // Estimated tokens (about 4 bytes each) the whole repo may use.
+    #[arg(long, required_unless_present = "max_total_bytes")]
+    pub(crate) max_total_tokens: Option<usize>,
+
+    #[arg(long)]
+    pub(crate) max_total_bytes: Option<usize>,
+
+    #[arg(long, default_value_t = false)]
+    pub(crate) include_hidden: bool,
+
+    #[arg(long, action = clap::ArgAction::Append)]
+    pub(crate) include: Vec<String>,
+
+    #[arg(long, action = clap::ArgAction::Append)]
+    pub(crate) exclude: Vec<String>,
+
+    // Config file to use instead of searching ancestors (as for `pack`).
+    #[arg(long)]
+    pub(crate) config: Option<PathBuf>,
+
+    // Apply the config's [profiles.NAME] keys on top of the rest of the config.
+    #[arg(long, conflicts_with = "no_config")]
+    pub(crate) profile: Option<String>,
+
+    // Ignore dumpo.toml and .dumpoinclude; the files are otherwise chosen as `pack` would.
+    #[arg(long, default_value_t = false)]
+    pub(crate) no_config: bool,
+}
+
+#[derive(Args, Debug, Clone, Default)]
+pub(crate) struct UnpackArgs {
+    // Markdown dump to read; stdin when omitted.
+    pub(crate) input: Option<PathBuf>,
 
-    #[arg(long, default_value_t = cfg!(target_os = "macos"))]
-    pub(crate) clipboard: bool,
+    // Directory the files are written under.
+    #[arg(long, default_value = ".")]
+    pub(crate) out: PathBuf,
+
+    // Heading prefix the dump was made with; falls back to config, then "## ".
+    #[arg(long)]
+    pub(crate) file_heading_prefix: Option<String>,
+
+    // Ignore dumpo.toml (file_heading_prefix is read from it otherwise).
+    #[arg(long, default_value_t = false)]
+    pub(crate) no_config: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    Pack(PackArgs),
+    Pack(Box<PackArgs>),
+    /// Check off files in a terminal list, then dump them (takes the same flags as pack)
+    Pick(Box<PackArgs>),
+    /// Recreate the files of a markdown dump on disk
+    Unpack(UnpackArgs),
+    /// Exit non-zero if the repo would not fit the given token/byte budget
+    Check(CheckArgs),
+    /// Print a shell completion script to stdout
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Print version, git commit, target triple and build profile
+    Version,
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Pack(args) => pack::run_pack(args),
+        Commands::Pack(args) => pack::run_pack(*args),
+        Commands::Pick(args) => pick::run_pick(*args),
+        Commands::Unpack(args) => unpack::run_unpack(args),
+        Commands::Check(args) => check::run_check(args),
+        Commands::Completions { shell } => completions::run_completions(shell),
+        Commands::Version => version::run_version(),
     }
 }
@@ -1,32 +1,379 @@
-use crate::clipboard::copy_to_clipboard;
-use crate::config::DumpoConfig;
-use crate::dump::build_dump_bytes;
-use crate::selector::Selector;
+use crate::cache::{default_cache_dir, CacheEntry};
+use crate::clipboard::{copy_to_clipboard, ClipboardUnavailable, DEFAULT_CLIPBOARD_TIMEOUT};
+use crate::config::{load_include_file, read_pattern_file, DumpoConfig};
+use crate::dump::{
+    build_dump_bytes, collect_files_sorted, default_fixture_dirs, default_generated_markers,
+    selection_fingerprint, sha256_hex, DumpOptions, DumpReport, ManifestEntry,
+    DEFAULT_BINARY_SAMPLE_BYTES, DEFAULT_FIXTURE_MAX_BYTES, DEFAULT_MAX_REPLACEMENT_RATIO,
+};
+use crate::entropy::{
+    EntropyAction, EntropyScan, DEFAULT_ENTROPY_MIN_LEN, DEFAULT_ENTROPY_THRESHOLD,
+};
+use crate::filter::excluded_filenames;
+use crate::format as fmt;
+use crate::git;
+use crate::selector::{rel_slash, Selector};
+use crate::style;
+use crate::units::format_bytes;
 use crate::PackArgs;
 use anyhow::{Context, Result};
-use std::io::{self, Write};
+use regex::bytes::Regex;
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::time::Duration;
 
 const DEFAULT_MAX_FILE_BYTES: usize = 20_000;
 const DEFAULT_MAX_TOTAL_BYTES: usize = 400_000;
+const DEFAULT_STDOUT: bool = !cfg!(target_os = "macos");
+const DEFAULT_CLIPBOARD: bool = cfg!(target_os = "macos");
 
 pub(crate) fn run_pack(args: PackArgs) -> Result<()> {
-    let root = args
-        .path
-        .canonicalize()
-        .with_context(|| format!("failed to canonicalize path: {}", args.path.display()))?;
+    let color = io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none();
 
-    let (cfg_path, cfg) = load_config_for_pack(&root, &args)?;
+    let mut stdout = io::stdout().lock();
+    let mut stderr = io::stderr().lock();
+    let mut diag = Diagnostics {
+        out: &mut stderr,
+        verbose: args.verbose,
+        quiet: args.quiet,
+        color,
+    };
+    pack_with(args, &mut stdout, copy_to_clipboard, &mut diag)
+}
+
+type CopyFn = fn(&[u8], Duration) -> Result<()>;
+
+// The root, options and selector `pack` would dump with these args (for `check`).
+pub(crate) fn resolve_quietly(args: &PackArgs) -> Result<(PathBuf, DumpOptions, Selector)> {
+    let mut sink = io::sink();
+    let mut diag = Diagnostics {
+        out: &mut sink,
+        verbose: false,
+        quiet: true,
+        color: false,
+    };
+    let resolved = resolve_pack(args, &mut diag)?;
+    Ok((resolved.root, resolved.opts, resolved.selector))
+}
+
+// Repo-relative paths of the files `pack` would consider with these args (the `pick` list).
+pub(crate) fn candidate_files(args: &PackArgs) -> Result<Vec<PathBuf>> {
+    let mut sink = io::sink();
+    let mut diag = Diagnostics {
+        out: &mut sink,
+        verbose: false,
+        quiet: true,
+        color: false,
+    };
+    let resolved = resolve_pack(args, &mut diag)?;
+    if resolved.root.is_file() {
+        anyhow::bail!("'{}' is not a directory", args.path.display());
+    }
+
+    let mut report = DumpReport::default();
+    let files = collect_files_sorted(
+        &resolved.root,
+        &resolved.opts,
+        &resolved.selector,
+        &mut report,
+    );
+    Ok(files.into_iter().map(|(rel, _)| rel).collect())
+}
+
+fn pack_with(
+    args: PackArgs,
+    stdout: &mut dyn Write,
+    copy: CopyFn,
+    diag: &mut Diagnostics,
+) -> Result<()> {
+    let ResolvedPack {
+        root,
+        opts,
+        selector,
+        to_stdout,
+        to_clipboard,
+        clipboard_timeout,
+        error_on_empty,
+    } = resolve_pack(&args, diag)?;
+
+    let cache = if args.cache {
+        Some(cache_entry(&args, &root, &opts, &selector)?)
+    } else {
+        None
+    };
+    let (bytes, report) = match cache.as_ref().and_then(|c| Some((c, c.load()?))) {
+        Some((entry, (bytes, report))) => {
+            diag.verbose(format_args!("cache hit: {}", entry.path().display()))?;
+            (bytes, report)
+        }
+        None => {
+            // The cache fingerprint already walked (and counted) these files.
+            selector.reset_counts();
+            let (bytes, report) = build_dump_bytes(&root, &opts, &selector)?;
+            // Checked before caching, so a cache hit is never an empty dump.
+            if error_on_empty && report.files_included == 0 {
+                anyhow::bail!("no files matched; nothing to dump (--error-on-empty)");
+            }
+            if let Some(entry) = &cache {
+                entry.store(&bytes, &report)?;
+                diag.verbose(format_args!(
+                    "cache miss, stored {}",
+                    entry.path().display()
+                ))?;
+            }
+            (bytes, report)
+        }
+    };
+
+    for warning in &report.warnings {
+        diag.verbose(warning)?;
+    }
+    if let Some(scan) = &opts.entropy_scan {
+        let outcome = match scan.action {
+            EntropyAction::Redact => "redacted",
+            EntropyAction::Skip => "file skipped",
+        };
+        for (path, count) in &report.entropy_flagged {
+            diag.warn(format_args!(
+                "{} high-entropy strings in {} ({outcome})",
+                count,
+                path.display()
+            ))?;
+        }
+    }
+    for (path, len) in &report.too_large_to_read {
+        diag.warn(format_args!(
+            "skipped {} unread: {} is over max_read_bytes",
+            path.display(),
+            format_bytes(*len as usize, args.exact_bytes)
+        ))?;
+    }
+    if !report.fixtures.is_empty() {
+        let paths: Vec<String> = report
+            .fixtures
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect();
+        let action = if opts.trim_fixtures {
+            "trimmed"
+        } else {
+            "found"
+        };
+        diag.verbose(format_args!(
+            "{action} {} probable fixtures: {}",
+            paths.len(),
+            paths.join(", ")
+        ))?;
+    }
+    if args.plain_secrets_warning {
+        let paths: Vec<String> = report
+            .secrets_skipped
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect();
+        if paths.is_empty() {
+            diag.info("protected 0 secret files")?;
+        } else {
+            diag.info(format_args!(
+                "protected {} secret files: {}",
+                paths.len(),
+                paths.join(", ")
+            ))?;
+        }
+    }
+    for path in &report.skipped_over_total {
+        diag.warn(format_args!(
+            "skipped {}: it didn't fit in the rest of max_total_bytes",
+            path.display()
+        ))?;
+    }
+    for (dir, skipped) in &report.dirs_capped {
+        diag.warn(format_args!(
+            "{dir}/ reached the per-dir byte cap; {skipped} files left out"
+        ))?;
+    }
+    for (group, skipped) in &report.dirs_over_share {
+        let slash = if group == fmt::ROOT_GROUP { "" } else { "/" };
+        diag.warn(format_args!(
+            "{group}{slash} used up its --balance-dirs share; {skipped} files left out"
+        ))?;
+    }
+    if opts.grep.is_some() {
+        diag.verbose(format_args!("grep dropped {} files", report.grep_dropped))?;
+    }
+    let matched = selector.report();
+    for (kind, counts) in [("include", &matched.include), ("exclude", &matched.exclude)] {
+        for (pattern, hits) in counts {
+            diag.verbose(format_args!("{kind} {pattern:?} matched {hits} files"))?;
+        }
+    }
+    diag.verbose(format_args!(
+        "wrote {} ({} files included, {} skipped)",
+        format_bytes(report.bytes, args.exact_bytes),
+        report.files_included,
+        report.files_skipped()
+    ))?;
+
+    if let Some(path) = &args.manifest {
+        write_manifest(path, &report.manifest)?;
+    }
+
+    if args.hash_only {
+        return writeln!(stdout, "{}", sha256_hex(&bytes)).context("failed writing to stdout");
+    }
+    if args.print_hash {
+        diag.info(format_args!("sha256={}", sha256_hex(&bytes)))?;
+    }
+
+    if !to_clipboard && !to_stdout && args.output.is_none() {
+        anyhow::bail!("no output selected (use --stdout, --clipboard and/or --output)");
+    }
+
+    // The dump was rendered once above; every enabled sink writes these same bytes.
+    if let Some(path) = &args.output {
+        write_output_file(path, &bytes, args.append)?;
+    }
+
+    let mut to_stdout = to_stdout;
+    if to_clipboard {
+        match copy(&bytes, clipboard_timeout) {
+            Ok(()) => {}
+            Err(e) if args.clipboard_fallback_stdout && e.is::<ClipboardUnavailable>() => {
+                diag.warn(format_args!("{e}; writing to stdout instead"))?;
+                to_stdout = true;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    if to_stdout {
+        stdout
+            .write_all(&bytes)
+            .context("failed writing to stdout")?;
+    }
+
+    if args.lang_summary {
+        for (language, stats) in &report.languages {
+            diag.info(format_args!(
+                "{language}: {} files, {}",
+                stats.files,
+                format_bytes(stats.bytes, args.exact_bytes)
+            ))?;
+        }
+    }
+    if args.token_summary {
+        for (language, tokens) in report.tokens_by_language() {
+            diag.info(format_args!("{language}: ~{tokens} tokens"))?;
+        }
+    }
+
+    // Sinks still get the (truncated) dump; only the exit status changes.
+    if args.fail_on_truncate && report.truncated() {
+        return Err(DumpTruncated {
+            files: report.files_truncated,
+            total: report.total_truncated,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+// Keyed on the dumpo version, the CLI flags, the config file's contents and the selection
+// fingerprint, so any change to what would be dumped (or how) misses.
+fn cache_entry(
+    args: &PackArgs,
+    root: &Path,
+    opts: &DumpOptions,
+    selector: &Selector,
+) -> Result<CacheEntry> {
+    let dir = args
+        .cache_dir
+        .clone()
+        .or_else(default_cache_dir)
+        .context("no cache directory: set XDG_CACHE_HOME or HOME, or pass --cache-dir")?;
+
+    let (cfg_path, _) = load_config_for_pack(root, args)?;
+    let cfg = match &cfg_path {
+        Some(path) => fs::read_to_string(path)
+            .with_context(|| format!("failed to read config: {}", path.display()))?,
+        None => String::new(),
+    };
+    let files = selection_fingerprint(root, opts, selector)?.join("\n");
+
+    Ok(CacheEntry::new(
+        &dir,
+        &[
+            env!("CARGO_PKG_VERSION"),
+            &format!("{args:?}"),
+            &root.display().to_string(),
+            &cfg,
+            &files,
+        ],
+    ))
+}
+
+// Settings resolved from CLI flags > config > defaults, ready to dump.
+struct ResolvedPack {
+    root: PathBuf,
+    opts: DumpOptions,
+    selector: Selector,
+    to_stdout: bool,
+    to_clipboard: bool,
+    clipboard_timeout: Duration,
+    error_on_empty: bool,
+}
+
+fn resolve_pack(args: &PackArgs, diag: &mut Diagnostics) -> Result<ResolvedPack> {
+    let root = resolve_root(&args.path)?;
+    // Display only: the walk always uses the resolved root.
+    let root_display = match &args.root_label {
+        Some(label) => PathBuf::from(label),
+        None => display_root_path(&args.path),
+    };
+
+    let (cfg_path, cfg) = load_config_for_pack(&root, args)?;
+    // Profile keys sit above the base config and below CLI flags.
+    let cfg = match &args.profile {
+        Some(name) => cfg.with_profile(name)?,
+        None => cfg,
+    };
+    let secrets = cfg.secret_rules();
 
     // Resolve effective settings
-    let max_file_bytes = args
-        .max_file_bytes
-        .or(cfg.max_file_bytes)
-        .unwrap_or(DEFAULT_MAX_FILE_BYTES);
+    let explicit_max_file_bytes = args.max_file_bytes.or(cfg.max_file_bytes);
+    let max_file_bytes = explicit_max_file_bytes.unwrap_or(DEFAULT_MAX_FILE_BYTES);
 
     let max_total_bytes = args
         .max_total_bytes
         .or(cfg.max_total_bytes)
         .unwrap_or(DEFAULT_MAX_TOTAL_BYTES);
+    // No single file could ever use its whole cap; probably a mistyped limit.
+    // The default per-file cap doesn't count: the user only lowered the total.
+    if explicit_max_file_bytes.is_some() && max_file_bytes > max_total_bytes {
+        let msg = format!(
+            "max_file_bytes ({max_file_bytes}) is larger than max_total_bytes ({max_total_bytes})"
+        );
+        if args.strict {
+            anyhow::bail!("{msg} (--strict)");
+        }
+        diag.warn(msg)?;
+    }
+    let total_limit_mode = args
+        .total_limit_mode
+        .or(cfg.total_limit_mode)
+        .unwrap_or_default();
+
+    let max_files = args.max_files.or(cfg.max_files);
+    let max_files_mode = args
+        .max_files_mode
+        .or(cfg.max_files_mode)
+        .unwrap_or_default();
+
+    let max_replacement_ratio = args
+        .max_replacement_ratio
+        .or(cfg.max_replacement_ratio)
+        .unwrap_or(DEFAULT_MAX_REPLACEMENT_RATIO);
 
     let include_hidden = args
         .include_hidden
@@ -34,95 +381,532 @@ pub(crate) fn run_pack(args: PackArgs) -> Result<()> {
         .or(cfg.include_hidden)
         .unwrap_or(false);
 
-    let (include_from_cli, include) = if !args.include.is_empty() {
-        (true, args.include)
+    let truncation_footer = args
+        .truncation_footer
+        .clone()
+        .or(cfg.truncation_footer)
+        .unwrap_or_else(|| fmt::DEFAULT_TRUNCATION_FOOTER.to_string());
+
+    let file_truncated_marker = args
+        .file_truncated_marker
+        .clone()
+        .or(cfg.file_truncated_marker)
+        .unwrap_or_else(|| fmt::DEFAULT_FILE_TRUNCATED_MARKER.to_string());
+
+    let to_stdout = args
+        .stdout
+        .or(args.no_stdout)
+        .or(cfg.stdout)
+        .unwrap_or(DEFAULT_STDOUT);
+
+    let to_clipboard = args
+        .clipboard
+        .or(args.no_clipboard)
+        .or(cfg.clipboard)
+        .unwrap_or(DEFAULT_CLIPBOARD);
+
+    let clipboard_timeout = args
+        .clipboard_timeout
+        .or(cfg.clipboard_timeout_secs.map(Duration::from_secs))
+        .unwrap_or(DEFAULT_CLIPBOARD_TIMEOUT);
+
+    let cfg_error_on_empty = cfg.error_on_empty.unwrap_or(false);
+    let format = args.format.or(cfg.format).unwrap_or_default();
+    let fence_char = args.fence_char.or(cfg.fence_char).unwrap_or_default();
+    let language_overrides =
+        fmt::LanguageOverrides::new(cfg.language_overrides.unwrap_or_default())?;
+    let file_heading_prefix = args
+        .file_heading_prefix
+        .clone()
+        .or(cfg.file_heading_prefix)
+        .unwrap_or_else(|| fmt::DEFAULT_FILE_HEADING_PREFIX.to_string());
+    fmt::validate_file_heading_prefix(&file_heading_prefix)?;
+
+    let cfg_base = cfg
+        .globs_relative_to_config
+        .unwrap_or(false)
+        .then(|| config_base(&root, cfg_path.as_deref()))
+        .flatten();
+
+    let cli_include = with_pattern_file(&args.include, args.include_from.as_deref())?;
+    let cli_exclude = with_pattern_file(&args.exclude, args.exclude_from.as_deref())?;
+
+    let (include_from_cli, include) = if !cli_include.is_empty() {
+        (true, cli_include)
     } else {
         (false, cfg.include.unwrap_or_default())
     };
+    let cfg_includes = if include_from_cli { 0 } else { include.len() };
+    // A .dumpoinclude allowlist adds to (never replaces) the includes above.
+    let include_file = if !args.no_config && !args.skip_include_file {
+        load_include_file(&root)?
+    } else {
+        None
+    };
 
-    let (exclude_from_cli, exclude) = if !args.exclude.is_empty() {
-        (true, args.exclude)
+    let (exclude_from_cli, exclude) = if !cli_exclude.is_empty() {
+        (true, cli_exclude)
     } else {
         (false, cfg.exclude.unwrap_or_default())
     };
 
-    if args.verbose {
+    if diag.is_verbose() {
         let cfg_display = cfg_path
             .as_ref()
             .map(|p| p.display().to_string())
             .unwrap_or_else(|| "<none>".to_string());
 
-        eprintln!(
-            "dumpo: root={} config={} max_file_bytes={} max_total_bytes={} include_hidden={} {} {} stdout={} clipboard={}",
-            root.display(),
-            cfg_display,
-            max_file_bytes,
-            max_total_bytes,
-            include_hidden,
-            summarize_patterns("include", include_from_cli, &include),
+        let modified_within = args
+            .modified_within
+            .map(|d| format!("{}s", d.as_secs()))
+            .unwrap_or_else(|| "<none>".to_string());
+
+        diag.settings(&[
+            Setting::plain("root", root.display()),
+            Setting::highlighted("config", cfg_display),
+            Setting::highlighted("max_file_bytes", max_file_bytes),
+            Setting::highlighted("max_total_bytes", max_total_bytes),
+            Setting::plain(
+                "max_files",
+                max_files.map_or_else(
+                    || "<none>".to_string(),
+                    |n| format!("{n} ({max_files_mode})"),
+                ),
+            ),
+            Setting::plain("include_hidden", include_hidden),
+            Setting::plain("format", format),
+            Setting::plain("modified_within", modified_within),
+            summarize_patterns(
+                "include",
+                include_from_cli,
+                &[
+                    include.as_slice(),
+                    include_file.as_ref().map_or(&[], |(_, p)| p),
+                ]
+                .concat(),
+            ),
             summarize_patterns("exclude", exclude_from_cli, &exclude),
-            args.stdout,
-            args.clipboard,
-        );
+            Setting::plain("stdout", to_stdout),
+            Setting::plain("clipboard", to_clipboard),
+        ])?;
+    }
+
+    let mut selector = match &cfg_base {
+        Some((dir, base)) => {
+            let (cfg_include, root_include) = include.split_at(cfg_includes);
+            let (cfg_exclude, root_exclude): (&[String], &[String]) = if exclude_from_cli {
+                (&[], &exclude)
+            } else {
+                (&exclude, &[])
+            };
+            let mut selector = Selector::for_root(&root, root_include, root_exclude)?;
+            selector.add_relative_to(dir, base, cfg_include, cfg_exclude)?;
+            selector
+        }
+        None => Selector::for_root(&root, &include, &exclude)?,
+    };
+    if let Some((path, patterns)) = &include_file {
+        // Its globs are relative to its own directory, which may be above the root.
+        match config_base(&root, Some(path)) {
+            Some((dir, base)) => selector.add_relative_to(&dir, &base, patterns, &[])?,
+            None => selector.add_relative_to(&root, "", patterns, &[])?,
+        }
     }
 
-    let selector = Selector::new(&include, &exclude)?;
+    let allow_hidden = match cfg.allow_hidden.as_deref() {
+        Some(patterns) if !patterns.is_empty() => {
+            Some(Selector::for_root(&root, patterns, &[]).context("allow_hidden")?)
+        }
+        _ => None,
+    };
+
+    let git_files = if args.include_git_files.is_empty() {
+        None
+    } else {
+        Some(Selector::new(&args.include_git_files, &[]).context("--include-git-files")?)
+    };
+    let force_include = if args.force_include.is_empty() {
+        None
+    } else {
+        Some(Selector::new(&args.force_include, &[]).context("--force-include")?)
+    };
+
+    let entropy_scan = args.scan_entropy.then(|| EntropyScan {
+        threshold: args
+            .entropy_threshold
+            .or(cfg.entropy_threshold)
+            .unwrap_or(DEFAULT_ENTROPY_THRESHOLD),
+        min_len: args
+            .entropy_min_len
+            .or(cfg.entropy_min_len)
+            .unwrap_or(DEFAULT_ENTROPY_MIN_LEN),
+        action: args.entropy_action.unwrap_or_default(),
+    });
+
+    let stdin_file = if args.stdin {
+        let mut content = Vec::new();
+        io::stdin()
+            .read_to_end(&mut content)
+            .context("failed reading stdin")?;
+        Some((PathBuf::from(&args.stdin_name), content))
+    } else {
+        None
+    };
+
+    let grep = args
+        .grep
+        .as_deref()
+        .map(|p| Regex::new(p).with_context(|| format!("--grep: invalid regex: {p:?}")))
+        .transpose()?;
+
+    let opts = DumpOptions {
+        top_largest: args.top_largest,
+        max_files,
+        max_files_mode,
+        total_limit_mode,
+        note_symlinks: args.note_symlinks,
+        show_sizes: args.show_sizes,
+        show_mode: args.show_mode,
+        compact: args.compact,
+        group_by_dir: args.group_by_dir,
+        exact_bytes: args.exact_bytes,
+        modified_within: args.modified_within,
+        grep,
+        languages: args.languages.clone(),
+        sort: args.sort,
+        ordered_includes: args.ordered_includes,
+        exclude_paths: args
+            .exclude_path
+            .iter()
+            .map(|p| p.replace('\\', "/").trim_start_matches("./").to_string())
+            .collect(),
+        changed_paths: args
+            .since
+            .as_deref()
+            .map(|reference| git::changed_since(&root, reference))
+            .transpose()?,
+        skip_non_utf8_paths: args.skip_non_utf8_paths,
+        truncation_footer,
+        file_truncated_marker,
+        path_prefix: args.path_prefix.clone(),
+        anonymize_paths: args.anonymize_paths,
+        respect_gitattributes: args.respect_gitattributes,
+        respect_gitignore: args.respect_gitignore,
+        walk_threads: args.walk_threads,
+        git_files,
+        allow_hidden,
+        find_hidden_secrets: args.plain_secrets_warning,
+        force_include,
+        manifest: args.manifest.is_some(),
+        respect_dockerignore: args.respect_dockerignore,
+        format,
+        fence_char,
+        language_overrides,
+        file_heading_prefix,
+        toc: args.toc.then(|| args.toc_style.unwrap_or_default()),
+        front_matter: args.front_matter,
+        list_binaries: args.list_binaries,
+        timestamp: args.timestamp,
+        no_header: args.no_header,
+        total_lines: args.total_lines,
+        root_display: Some(root_display),
+        stdin_file,
+        max_replacement_ratio,
+        binary_sample_bytes: cfg
+            .binary_sample_bytes
+            .unwrap_or(DEFAULT_BINARY_SAMPLE_BYTES),
+        max_read_bytes: args.max_read_bytes.or(cfg.max_read_bytes),
+        fixture_dirs: cfg.fixture_dirs.unwrap_or_else(default_fixture_dirs),
+        fixture_max_bytes: args
+            .fixture_max_bytes
+            .or(cfg.fixture_max_bytes)
+            .unwrap_or(DEFAULT_FIXTURE_MAX_BYTES),
+        trim_fixtures: args.trim_fixtures,
+        skip_generated: args.skip_generated,
+        include_lfs_pointers: args.include_lfs_pointers,
+        entropy_scan,
+        max_line_bytes: args.max_line_bytes,
+        line_numbers: args.line_numbers,
+        escape_fences: args.escape_fences,
+        per_dir_byte_cap: args.per_dir_byte_cap,
+        balance_dirs: args.balance_dirs,
+        excluded_filenames: excluded_filenames(args.include_lockfiles, args.include_meta),
+        secrets,
+        generated_markers: cfg
+            .generated_markers
+            .unwrap_or_else(default_generated_markers),
+        max_file_bytes_by_ext: cfg.max_file_bytes_by_ext.unwrap_or_default(),
+        max_file_bytes_by_language: cfg.max_file_bytes_by_language.unwrap_or_default(),
+        ..DumpOptions::new(max_file_bytes, max_total_bytes, include_hidden)
+    };
+
+    Ok(ResolvedPack {
+        root,
+        opts,
+        selector,
+        to_stdout,
+        to_clipboard,
+        clipboard_timeout,
+        error_on_empty: args.error_on_empty || cfg_error_on_empty,
+    })
+}
+
+// Inline patterns followed by those read from an --include-from/--exclude-from file.
+fn with_pattern_file(inline: &[String], file: Option<&Path>) -> Result<Vec<String>> {
+    let mut patterns = inline.to_vec();
+    if let Some(path) = file {
+        patterns.extend(read_pattern_file(path)?);
+    }
+    Ok(patterns)
+}
 
-    let bytes = build_dump_bytes(
-        &root,
-        max_file_bytes,
-        max_total_bytes,
-        include_hidden,
-        &selector,
-    )?;
+// Returned under --fail-on-truncate so callers can tell it apart from other failures.
+#[derive(Debug, Clone, Copy)]
+struct DumpTruncated {
+    files: usize,
+    total: bool,
+}
 
-    if !args.clipboard && !args.stdout {
-        anyhow::bail!("no output selected (use --stdout and/or --clipboard)");
+impl std::fmt::Display for DumpTruncated {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "dump was truncated ({} files cut short", self.files)?;
+        if self.total {
+            write!(f, ", max_total_bytes reached")?;
+        }
+        write!(f, ")")
     }
+}
+
+impl std::error::Error for DumpTruncated {}
 
-    if args.clipboard {
-        copy_to_clipboard(&bytes)?;
+fn write_manifest(path: &Path, entries: &[ManifestEntry]) -> Result<()> {
+    #[derive(serde::Serialize)]
+    struct Manifest<'a> {
+        schema_version: u32,
+        files: &'a [ManifestEntry],
     }
 
-    if args.stdout {
-        let mut out = io::stdout().lock();
-        out.write_all(&bytes).context("failed writing to stdout")?;
+    let manifest = Manifest {
+        schema_version: fmt::SCHEMA_VERSION,
+        files: entries,
+    };
+    let mut json = serde_json::to_vec_pretty(&manifest)?;
+    json.push(b'\n');
+    fs::write(path, json).with_context(|| format!("failed to write manifest: {}", path.display()))
+}
+
+fn write_output_file(path: &Path, bytes: &[u8], append: bool) -> Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)
+        .with_context(|| format!("failed to open output file: {}", path.display()))?;
+
+    let separate = append && file.metadata().map(|m| m.len() > 0).unwrap_or(false);
+    if separate {
+        file.write_all(b"\n")
+            .with_context(|| format!("failed writing to {}", path.display()))?;
     }
 
-    Ok(())
+    file.write_all(bytes)
+        .with_context(|| format!("failed writing to {}", path.display()))
+}
+
+// Single place for stderr chatter so --verbose and --quiet are honored consistently.
+struct Diagnostics<'a> {
+    out: &'a mut dyn Write,
+    verbose: bool,
+    quiet: bool,
+    // Multi-line, ANSI-colored settings output (only when stderr is a terminal).
+    color: bool,
+}
+
+struct Setting {
+    label: String,
+    value: String,
+    highlight: bool,
+}
+
+impl Setting {
+    fn plain(label: impl Into<String>, value: impl std::fmt::Display) -> Self {
+        Self {
+            label: label.into(),
+            value: value.to_string(),
+            highlight: false,
+        }
+    }
+
+    fn highlighted(label: impl Into<String>, value: impl std::fmt::Display) -> Self {
+        Self {
+            highlight: true,
+            ..Self::plain(label, value)
+        }
+    }
+}
+
+impl Diagnostics<'_> {
+    fn is_verbose(&self) -> bool {
+        self.verbose && !self.quiet
+    }
+
+    // Shown unless --quiet.
+    fn info(&mut self, msg: impl std::fmt::Display) -> Result<()> {
+        if !self.quiet {
+            writeln!(self.out, "dumpo: {msg}").context("failed writing to stderr")?;
+        }
+        Ok(())
+    }
+
+    fn warn(&mut self, msg: impl std::fmt::Display) -> Result<()> {
+        self.info(format_args!("warning: {msg}"))
+    }
+
+    fn verbose(&mut self, msg: impl std::fmt::Display) -> Result<()> {
+        if self.is_verbose() {
+            writeln!(self.out, "dumpo: {msg}").context("failed writing to stderr")?;
+        }
+        Ok(())
+    }
+
+    // One `label=value` line when plain; an aligned, colored table on a terminal.
+    fn settings(&mut self, settings: &[Setting]) -> Result<()> {
+        if !self.is_verbose() {
+            return Ok(());
+        }
+
+        if !self.color {
+            let line = settings
+                .iter()
+                .map(|s| format!("{}={}", s.label, s.value))
+                .collect::<Vec<_>>()
+                .join(" ");
+            return self.verbose(line);
+        }
+
+        let width = settings.iter().map(|s| s.label.len()).max().unwrap_or(0);
+        let mut block = format!("dumpo: {}\n", style::dim("settings", true));
+        for s in settings {
+            let label = style::dim(&format!("{:width$}", s.label), true);
+            let value = if s.highlight {
+                style::highlight(&s.value, true)
+            } else {
+                s.value.clone()
+            };
+            block.push_str(&format!("  {label}  {value}\n"));
+        }
+        self.out
+            .write_all(block.as_bytes())
+            .context("failed writing to stderr")
+    }
+}
+
+use std::path::{Component, Path, PathBuf};
+
+pub(crate) fn resolve_root(path: &Path) -> Result<PathBuf> {
+    if !path.exists() {
+        anyhow::bail!("path '{}' does not exist", path.display());
+    }
+
+    path.canonicalize()
+        .with_context(|| format!("failed to canonicalize path: {}", path.display()))
+}
+
+// The root as the user spelled it, made absolute without resolving symlinks, so a
+// symlinked working directory shows up under its own name in the `root:` line.
+// `..` is resolved lexically, against the spelled path rather than the real one.
+fn display_root_path(path: &Path) -> PathBuf {
+    let joined = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        match logical_cwd() {
+            Some(cwd) => cwd.join(path),
+            None => path.to_path_buf(),
+        }
+    };
+
+    let mut resolved = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match resolved.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    resolved.pop();
+                }
+                // `/..` is `/`; a leading `..` with no cwd to resolve against stays.
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => resolved.push(component),
+            },
+            _ => resolved.push(component),
+        }
+    }
+    resolved
+}
+
+// $PWD keeps the symlinked spelling of the working directory; current_dir() does not.
+fn logical_cwd() -> Option<PathBuf> {
+    let cwd = std::env::current_dir().ok()?;
+    let pwd = std::env::var_os("PWD").map(PathBuf::from);
+
+    match pwd {
+        Some(pwd) if pwd.is_absolute() && pwd.canonicalize().ok() == cwd.canonicalize().ok() => {
+            Some(pwd)
+        }
+        _ => Some(cwd),
+    }
 }
 
-use std::path::{Path, PathBuf};
+const CONFIG_ENV_VAR: &str = "DUMPO_CONFIG";
+
+// The config file's directory and the root's path beneath it ("crates/a"), for matching
+// config globs relative to the config file. None when the root is that directory or
+// lies outside it (e.g. a --config elsewhere); globs are then root-relative as usual.
+fn config_base(root: &Path, cfg_path: Option<&Path>) -> Option<(PathBuf, String)> {
+    let dir = cfg_path?.parent()?.canonicalize().ok()?;
+    let base = rel_slash(root.strip_prefix(&dir).ok()?);
+    (!base.is_empty()).then_some((dir, base))
+}
 
+// Precedence: --no-config > --config > $DUMPO_CONFIG > nearest dumpo.toml.
 fn load_config_for_pack(root: &Path, args: &PackArgs) -> Result<(Option<PathBuf>, DumpoConfig)> {
+    let env_path = std::env::var_os(CONFIG_ENV_VAR)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from);
+    load_config_with_env(root, args, env_path)
+}
+
+fn load_config_with_env(
+    root: &Path,
+    args: &PackArgs,
+    env_path: Option<PathBuf>,
+) -> Result<(Option<PathBuf>, DumpoConfig)> {
     if args.no_config {
         return Ok((None, DumpoConfig::default()));
     }
 
-    if let Some(path) = &args.config {
-        let s = std::fs::read_to_string(path)
-            .with_context(|| format!("failed to read config: {}", path.display()))?;
-        let cfg = toml::from_str::<DumpoConfig>(&s)
-            .with_context(|| format!("failed to parse config: {}", path.display()))?;
-        return Ok((Some(path.clone()), cfg));
+    if let Some(path) = args.config.clone().or(env_path) {
+        let cfg = DumpoConfig::load_file(&path)?;
+        return Ok((Some(path), cfg));
     }
 
     DumpoConfig::load_nearest(root)
 }
 
-fn summarize_patterns(label: &str, from_cli: bool, patterns: &[String]) -> String {
+fn summarize_patterns(label: &str, from_cli: bool, patterns: &[String]) -> Setting {
     if patterns.is_empty() {
-        return format!("{label}=<none>");
+        return Setting::plain(label, "<none>");
     }
 
     let n = patterns.len().min(20);
     let head = patterns[..n].join(", ");
     let src = if from_cli { "cli" } else { "config" };
+    let label = format!("{label}({src})");
 
     if patterns.len() > n {
-        format!("{label}({src})=[{head}, ...] (n={})", patterns.len())
+        Setting::plain(label, format!("[{head}, ...] (n={})", patterns.len()))
     } else {
-        format!("{label}({src})=[{head}] (n={})", patterns.len())
+        Setting::plain(label, format!("[{head}] (n={})", patterns.len()))
     }
 }
 
@@ -130,24 +914,235 @@ fn summarize_patterns(label: &str, from_cli: bool, patterns: &[String]) -> Strin
 mod tests {
     use super::*;
     use crate::testutil::TempRepo;
+    use crate::units::human_bytes;
+
+    // Runs a pack into in-memory sinks and returns (stdout, stderr).
+    fn run_captured(args: PackArgs, color: bool) -> (String, String) {
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let mut diag = Diagnostics {
+            out: &mut stderr,
+            verbose: args.verbose,
+            quiet: args.quiet,
+            color,
+        };
+        pack_with(args, &mut stdout, copy_to_clipboard, &mut diag).unwrap();
+
+        (
+            String::from_utf8(stdout).unwrap(),
+            String::from_utf8(stderr).unwrap(),
+        )
+    }
 
     fn base_args(repo: &TempRepo) -> PackArgs {
         PackArgs {
             path: repo.path().to_path_buf(),
-            max_file_bytes: None,
-            max_total_bytes: None,
-            include_hidden: None,
-            no_include_hidden: None,
-            verbose: false,
-            include: vec![],
-            exclude: vec![],
-            config: None,
-            no_config: false,
-            stdout: true,
-            clipboard: false,
+            stdout: Some(true),
+            clipboard: Some(false),
+            ..Default::default()
         }
     }
 
+    #[test]
+    fn cache_hits_until_a_file_or_option_changes() {
+        let repo = TempRepo::new();
+        let cache = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+
+        let args = PackArgs {
+            cache: true,
+            cache_dir: Some(cache.path().to_path_buf()),
+            verbose: true,
+            include: vec!["**/*.rs".to_string()],
+            ..base_args(&repo)
+        };
+        let (first, err) = run_captured(args.clone(), false);
+        assert!(err.contains("cache miss, stored"));
+        assert!(err.contains("include \"**/*.rs\" matched 1 files"));
+        let (second, err) = run_captured(args.clone(), false);
+        assert!(err.contains("cache hit: "));
+        assert!(err.contains("include \"**/*.rs\" matched 1 files"));
+        assert!(err.contains("1 files included"));
+        assert_eq!(second, first);
+
+        let (_, err) = run_captured(
+            PackArgs {
+                max_file_bytes: Some(5),
+                ..args.clone()
+            },
+            false,
+        );
+        assert!(err.contains("cache miss"));
+
+        repo.write("src/lib.rs", "pub fn lib() -> u8 { 1 }\n");
+        let (third, err) = run_captured(args, false);
+        assert!(err.contains("cache miss"));
+        assert!(third.contains("-> u8"));
+    }
+
+    #[test]
+    fn cache_hit_repeats_the_report_warnings() {
+        let repo = TempRepo::new();
+        let cache = TempRepo::new();
+        repo.write(
+            "src/lib.rs",
+            "let token = \"dGhpcyBpcyBhIHNlY3JldCB0b2tlbiBrZXkgMTIzNDU2Nzg5MA==\";\n",
+        );
+        repo.write("server.pem", "-----BEGIN-----\n");
+
+        let args = PackArgs {
+            cache: true,
+            cache_dir: Some(cache.path().to_path_buf()),
+            scan_entropy: true,
+            plain_secrets_warning: true,
+            ..base_args(&repo)
+        };
+        let (first, miss) = run_captured(args.clone(), false);
+        let (second, hit) = run_captured(args, false);
+        assert!(miss.contains("high-entropy strings in src/lib.rs"));
+        assert!(miss.contains("server.pem"));
+        assert_eq!(hit, miss);
+        assert_eq!(second, first);
+    }
+
+    #[test]
+    fn error_on_empty_rejects_a_dump_without_files() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+
+        let args = PackArgs {
+            no_config: true,
+            include: vec!["no/such/**".to_string()],
+            ..base_args(&repo)
+        };
+        let (out, _) = run_captured(args.clone(), false);
+        assert!(out.starts_with(fmt::DUMP_TITLE));
+
+        let strict = PackArgs {
+            error_on_empty: true,
+            ..args
+        };
+        let mut stdout = Vec::new();
+        let mut diag = Diagnostics {
+            out: &mut io::sink(),
+            verbose: false,
+            quiet: false,
+            color: false,
+        };
+        let err = pack_with(strict, &mut stdout, copy_to_clipboard, &mut diag).unwrap_err();
+        assert!(err.to_string().contains("no files matched"));
+        assert!(stdout.is_empty());
+    }
+
+    #[test]
+    fn max_file_bytes_above_max_total_bytes_warns_or_fails_with_strict() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+
+        let args = PackArgs {
+            no_config: true,
+            max_file_bytes: Some(500_000),
+            max_total_bytes: Some(100_000),
+            ..base_args(&repo)
+        };
+        let (out, err) = run_captured(args.clone(), false);
+        assert!(out.contains("## src/lib.rs"));
+        assert!(err.contains(
+            "dumpo: warning: max_file_bytes (500000) is larger than max_total_bytes (100000)"
+        ));
+
+        let (_, err) = run_captured(
+            PackArgs {
+                max_file_bytes: Some(100_000),
+                ..args.clone()
+            },
+            false,
+        );
+        assert!(!err.contains("warning"));
+
+        // Only --max-total-bytes: the default max_file_bytes is above it, but wasn't asked for.
+        let (out, err) = run_captured(
+            PackArgs {
+                max_file_bytes: None,
+                max_total_bytes: Some(1_000),
+                strict: true,
+                ..args.clone()
+            },
+            false,
+        );
+        assert!(out.contains("## src/lib.rs"));
+        assert!(!err.contains("warning"));
+
+        let strict = PackArgs {
+            strict: true,
+            ..args
+        };
+        let mut stdout = Vec::new();
+        let mut diag = Diagnostics {
+            out: &mut io::sink(),
+            verbose: false,
+            quiet: false,
+            color: false,
+        };
+        let err = pack_with(strict, &mut stdout, copy_to_clipboard, &mut diag).unwrap_err();
+        assert!(err.to_string().contains("--strict"));
+        assert!(stdout.is_empty());
+    }
+
+    #[test]
+    fn config_globs_can_be_relative_to_the_config_file() {
+        let repo = TempRepo::new();
+        repo.write(
+            "dumpo.toml",
+            "globs_relative_to_config = true\n\
+             include = [\"crates/a/src/**\", \"crates/b/**\"]\n\
+             exclude = [\"crates/a/src/gen.rs\"]\n",
+        );
+        repo.write("crates/a/src/lib.rs", "pub fn lib() {}\n");
+        repo.write("crates/a/src/gen.rs", "pub fn gen() {}\n");
+        repo.write("crates/a/README.md", "readme\n");
+
+        let args = PackArgs {
+            path: repo.path().join("crates/a"),
+            ..base_args(&repo)
+        };
+        let (out, _) = run_captured(args, false);
+        let headings: Vec<&str> = out.lines().filter_map(|l| l.strip_prefix("## ")).collect();
+        assert_eq!(headings, ["src/lib.rs"]);
+
+        // From the config's own directory the globs apply unchanged.
+        let (out, _) = run_captured(base_args(&repo), false);
+        let headings: Vec<&str> = out.lines().filter_map(|l| l.strip_prefix("## ")).collect();
+        assert_eq!(headings, ["crates/a/src/lib.rs"]);
+    }
+
+    #[test]
+    fn profile_applies_between_config_and_cli() {
+        let repo = TempRepo::new();
+        repo.write(
+            "dumpo.toml",
+            "max_total_bytes = 100000\n\n[profiles.slim]\nmax_total_bytes = 300\n",
+        );
+        repo.write("src/lib.rs", &"x".repeat(1000));
+
+        let (full, _) = run_captured(base_args(&repo), false);
+        assert!(full.len() > 1000);
+
+        let slim = PackArgs {
+            profile: Some("slim".to_string()),
+            ..base_args(&repo)
+        };
+        let (out, _) = run_captured(slim.clone(), false);
+        assert!(out.len() <= 300);
+
+        let cli_wins = PackArgs {
+            max_total_bytes: Some(600),
+            ..slim
+        };
+        let (out, _) = run_captured(cli_wins, false);
+        assert!(out.len() > 300 && out.len() <= 600);
+    }
+
     #[test]
     fn no_config_ignores_repo_dumpo_toml() {
         let repo = TempRepo::new();
@@ -184,4 +1179,719 @@ mod tests {
         assert!(path.unwrap().ends_with("custom.toml"));
         assert_eq!(cfg.max_total_bytes, Some(222));
     }
+
+    #[test]
+    fn resolve_root_reports_missing_path_clearly() {
+        let repo = TempRepo::new();
+        let missing = repo.path().join("does-not-exist");
+
+        let err = resolve_root(&missing).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("path '{}' does not exist", missing.display())
+        );
+    }
+
+    #[test]
+    fn resolve_root_accepts_single_file() {
+        let repo = TempRepo::new();
+        repo.write("file.txt", "x");
+
+        let root = resolve_root(&repo.path().join("file.txt")).unwrap();
+        assert!(root.is_file());
+    }
+
+    #[test]
+    fn env_config_overrides_nearest_search() {
+        let repo = TempRepo::new();
+        repo.write("dumpo.toml", "max_total_bytes = 111\n");
+        repo.write("ci.toml", "max_total_bytes = 333\n");
+
+        let args = base_args(&repo);
+        let root = args.path.canonicalize().unwrap();
+        let env_path = Some(repo.path().join("ci.toml"));
+        let (path, cfg) = load_config_with_env(&root, &args, env_path).unwrap();
+
+        assert!(path.unwrap().ends_with("ci.toml"));
+        assert_eq!(cfg.max_total_bytes, Some(333));
+    }
+
+    #[test]
+    fn explicit_config_overrides_env_config() {
+        let repo = TempRepo::new();
+        repo.write("ci.toml", "max_total_bytes = 333\n");
+        repo.write("custom.toml", "max_total_bytes = 222\n");
+
+        let mut args = base_args(&repo);
+        args.config = Some(repo.path().join("custom.toml"));
+
+        let root = args.path.canonicalize().unwrap();
+        let env_path = Some(repo.path().join("ci.toml"));
+        let (_path, cfg) = load_config_with_env(&root, &args, env_path).unwrap();
+
+        assert_eq!(cfg.max_total_bytes, Some(222));
+    }
+
+    #[test]
+    fn quiet_run_writes_nothing_to_stderr() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+
+        let args = PackArgs {
+            quiet: true,
+            no_config: true,
+            ..base_args(&repo)
+        };
+
+        let (stdout, stderr) = run_captured(args, false);
+
+        assert!(stdout.contains("## src/lib.rs"));
+        assert!(stderr.is_empty());
+    }
+
+    #[test]
+    fn quiet_conflicts_with_verbose() {
+        use clap::Parser;
+
+        let res = crate::Cli::try_parse_from(["dumpo", "pack", "--quiet", "--verbose"]);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn verbose_settings_are_plain_without_color() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+
+        let args = PackArgs {
+            verbose: true,
+            no_config: true,
+            ..base_args(&repo)
+        };
+        let (_stdout, stderr) = run_captured(args, false);
+
+        assert!(stderr.starts_with("dumpo: root="));
+        assert!(stderr.contains(" max_total_bytes=400000 "));
+        assert!(!stderr.contains('\x1b'));
+    }
+
+    #[test]
+    fn verbose_settings_are_multiline_with_color() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+
+        let args = PackArgs {
+            verbose: true,
+            no_config: true,
+            ..base_args(&repo)
+        };
+        let (_stdout, stderr) = run_captured(args, true);
+
+        assert!(stderr.contains('\x1b'));
+        assert!(stderr.lines().count() > 5);
+    }
+
+    #[test]
+    fn append_output_accumulates_runs() {
+        let repo = TempRepo::new();
+        repo.write("src/a.rs", "fn a() {}\n");
+        let out_dir = TempRepo::new();
+        let out_path = out_dir.path().join("prompt.md");
+
+        let first = PackArgs {
+            no_config: true,
+            stdout: Some(false),
+            output: Some(out_path.clone()),
+            append: true,
+            ..base_args(&repo)
+        };
+        run_captured(first.clone(), false);
+
+        repo.write("src/b.rs", "fn b() {}\n");
+        run_captured(first, false);
+
+        let s = fs::read_to_string(&out_path).unwrap();
+        assert_eq!(s.matches(fmt::DUMP_TITLE).count(), 2);
+        assert_eq!(s.matches("## src/a.rs").count(), 2);
+        assert_eq!(s.matches("## src/b.rs").count(), 1);
+        assert!(s.contains("```\n\n\n# dumpo pack"));
+    }
+
+    #[test]
+    fn output_without_append_truncates() {
+        let repo = TempRepo::new();
+        repo.write("src/a.rs", "fn a() {}\n");
+        let out_dir = TempRepo::new();
+        let out_path = out_dir.path().join("prompt.md");
+        fs::write(&out_path, "stale contents").unwrap();
+
+        let args = PackArgs {
+            no_config: true,
+            stdout: Some(false),
+            output: Some(out_path.clone()),
+            ..base_args(&repo)
+        };
+        run_captured(args, false);
+
+        let s = fs::read_to_string(&out_path).unwrap();
+        assert!(s.starts_with(fmt::DUMP_TITLE));
+        assert!(!s.contains("stale contents"));
+    }
+
+    #[test]
+    fn config_format_json_applies_without_cli_flag() {
+        let repo = TempRepo::new();
+        repo.write("dumpo.toml", "format = \"json\"\n");
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+
+        let (stdout, _stderr) = run_captured(base_args(&repo), false);
+
+        let v: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(v["files"][0]["path"], "src/lib.rs");
+        assert_eq!(v["files"][0]["content"], "pub fn lib() {}\n");
+    }
+
+    #[test]
+    fn cli_format_overrides_config_format() {
+        let repo = TempRepo::new();
+        repo.write("dumpo.toml", "format = \"json\"\n");
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+
+        let args = PackArgs {
+            format: Some(fmt::OutputFormat::Markdown),
+            ..base_args(&repo)
+        };
+        let (stdout, _stderr) = run_captured(args, false);
+
+        assert!(stdout.starts_with(fmt::DUMP_TITLE));
+        assert!(stdout.contains("## src/lib.rs"));
+    }
+
+    #[test]
+    fn config_stdout_enables_sink_without_cli_flag() {
+        let repo = TempRepo::new();
+        repo.write("dumpo.toml", "stdout = true\nclipboard = false\n");
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+
+        let args = PackArgs {
+            stdout: None,
+            clipboard: None,
+            ..base_args(&repo)
+        };
+        let (stdout, _stderr) = run_captured(args, false);
+
+        assert!(stdout.contains("## src/lib.rs"));
+    }
+
+    #[test]
+    fn cli_no_stdout_disables_config_enabled_sink() {
+        let repo = TempRepo::new();
+        repo.write("dumpo.toml", "stdout = true\nclipboard = false\n");
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+
+        let args = PackArgs {
+            stdout: None,
+            no_stdout: Some(false),
+            clipboard: None,
+            ..base_args(&repo)
+        };
+        let mut sink = Vec::new();
+        let mut stderr = Vec::new();
+        let mut diag = Diagnostics {
+            out: &mut stderr,
+            verbose: false,
+            quiet: false,
+            color: false,
+        };
+        let err = pack_with(args, &mut sink, copy_to_clipboard, &mut diag).unwrap_err();
+
+        assert!(err.to_string().starts_with("no output selected"));
+        assert!(sink.is_empty());
+    }
+
+    #[test]
+    fn sink_flags_are_absent_unless_passed() {
+        use clap::Parser;
+
+        let cli = crate::Cli::try_parse_from(["dumpo", "pack", "--no-clipboard"]).unwrap();
+        let crate::Commands::Pack(args) = cli.command else {
+            panic!("expected pack");
+        };
+
+        assert_eq!(args.stdout, None);
+        assert_eq!(args.no_clipboard, Some(false));
+        assert_eq!(args.include_hidden, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn root_line_shows_symlink_path_not_resolved_path() {
+        let repo = TempRepo::new();
+        repo.write("real/src/lib.rs", "pub fn lib() {}\n");
+        let link = repo.path().join("link");
+        std::os::unix::fs::symlink(repo.path().join("real"), &link).unwrap();
+
+        let args = PackArgs {
+            path: link.clone(),
+            no_config: true,
+            ..base_args(&repo)
+        };
+        let (stdout, _stderr) = run_captured(args, false);
+
+        assert!(stdout.contains(&fmt::root_line(&link)));
+        assert!(stdout.contains("## src/lib.rs"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn root_line_resolves_parent_dir_components() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+
+        let args = PackArgs {
+            path: repo.path().join("src/.."),
+            no_config: true,
+            ..base_args(&repo)
+        };
+        let (stdout, _stderr) = run_captured(args, false);
+        assert!(stdout.contains(&format!("{}\n", fmt::root_line(repo.path()))));
+
+        assert_eq!(
+            display_root_path(Path::new("/home/u/proj/./../other/..")),
+            Path::new("/home/u")
+        );
+        assert_eq!(display_root_path(Path::new("/../a")), Path::new("/a"));
+    }
+
+    #[test]
+    fn root_label_replaces_the_root_line_path() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+
+        let args = PackArgs {
+            root_label: Some("myproject".to_string()),
+            no_config: true,
+            ..base_args(&repo)
+        };
+        let (stdout, _) = run_captured(args, false);
+
+        assert!(stdout.contains("\n- root: myproject\n"));
+        assert!(!stdout.contains(&repo.path().display().to_string()));
+        assert!(stdout.contains("## src/lib.rs"));
+    }
+
+    #[test]
+    fn verbose_summary_reports_actual_dump_size() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+        fs::write(repo.path().join("src/blob.dat"), b"bin\0ary").unwrap();
+
+        let args = PackArgs {
+            verbose: true,
+            no_config: true,
+            ..base_args(&repo)
+        };
+        let (stdout, stderr) = run_captured(args.clone(), false);
+
+        assert!(stderr.contains(&format!(
+            "dumpo: wrote {} (1 files included, 1 skipped)",
+            human_bytes(stdout.len())
+        )));
+
+        let args = PackArgs {
+            exact_bytes: true,
+            ..args
+        };
+        let (stdout, stderr) = run_captured(args, false);
+        assert!(stderr.contains(&format!(
+            "dumpo: wrote {} bytes (1 files included, 1 skipped)",
+            stdout.len()
+        )));
+    }
+
+    fn no_clipboard_backend(_bytes: &[u8], _timeout: Duration) -> Result<()> {
+        Err(ClipboardUnavailable("no clipboard backend".to_string()).into())
+    }
+
+    thread_local! {
+        static CLIPBOARD: std::cell::RefCell<Vec<u8>> = const { std::cell::RefCell::new(Vec::new()) };
+    }
+
+    fn fake_clipboard(bytes: &[u8], _timeout: Duration) -> Result<()> {
+        CLIPBOARD.with(|c| *c.borrow_mut() = bytes.to_vec());
+        Ok(())
+    }
+
+    #[test]
+    fn clipboard_stdout_and_output_get_identical_bytes() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+        let out_dir = TempRepo::new();
+        let output = out_dir.path().join("dump.md");
+
+        let args = PackArgs {
+            no_config: true,
+            clipboard: Some(true),
+            output: Some(output.clone()),
+            ..base_args(&repo)
+        };
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let mut diag = Diagnostics {
+            out: &mut stderr,
+            verbose: false,
+            quiet: false,
+            color: false,
+        };
+        pack_with(args, &mut stdout, fake_clipboard, &mut diag).unwrap();
+
+        assert!(String::from_utf8_lossy(&stdout).contains("## src/lib.rs"));
+        assert_eq!(CLIPBOARD.with(|c| c.borrow().clone()), stdout);
+        assert_eq!(fs::read(&output).unwrap(), stdout);
+    }
+
+    #[test]
+    fn clipboard_fallback_writes_stdout_when_backend_missing() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+
+        let args = PackArgs {
+            no_config: true,
+            stdout: Some(false),
+            clipboard: Some(true),
+            clipboard_fallback_stdout: true,
+            ..base_args(&repo)
+        };
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let mut diag = Diagnostics {
+            out: &mut stderr,
+            verbose: false,
+            quiet: false,
+            color: false,
+        };
+        pack_with(args.clone(), &mut stdout, no_clipboard_backend, &mut diag).unwrap();
+
+        assert!(String::from_utf8(stdout).unwrap().contains("## src/lib.rs"));
+        assert!(String::from_utf8(stderr)
+            .unwrap()
+            .starts_with("dumpo: warning: no clipboard backend"));
+
+        let strict = PackArgs {
+            clipboard_fallback_stdout: false,
+            ..args
+        };
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let mut diag = Diagnostics {
+            out: &mut stderr,
+            verbose: false,
+            quiet: false,
+            color: false,
+        };
+        assert!(pack_with(strict, &mut stdout, no_clipboard_backend, &mut diag).is_err());
+        assert!(stdout.is_empty());
+    }
+
+    #[test]
+    fn hash_only_is_stable_and_tracks_content() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+
+        let args = PackArgs {
+            no_config: true,
+            hash_only: true,
+            ..base_args(&repo)
+        };
+        let (first, _) = run_captured(args.clone(), false);
+        let (second, _) = run_captured(args.clone(), false);
+
+        assert_eq!(first.trim().len(), 64);
+        assert_eq!(first, second);
+
+        repo.write("src/lib.rs", "pub fn lib() -> u8 { 1 }\n");
+        let (third, _) = run_captured(args, false);
+        assert_ne!(first, third);
+    }
+
+    #[test]
+    fn print_hash_goes_to_stderr_alongside_dump() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+
+        let args = PackArgs {
+            no_config: true,
+            print_hash: true,
+            ..base_args(&repo)
+        };
+        let (stdout, stderr) = run_captured(args, false);
+
+        assert_eq!(
+            stderr,
+            format!("dumpo: sha256={}\n", sha256_hex(stdout.as_bytes()))
+        );
+    }
+
+    #[test]
+    fn verbose_reports_per_pattern_matches() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+        repo.write("src/main.rs", "fn main() {}\n");
+        repo.write("README.md", "readme\n");
+
+        let args = PackArgs {
+            verbose: true,
+            no_config: true,
+            include: vec!["src/**".to_string(), "*.md".to_string()],
+            ..base_args(&repo)
+        };
+        let (_stdout, stderr) = run_captured(args, false);
+
+        assert!(stderr.contains("dumpo: include \"src/**\" matched 2 files\n"));
+        assert!(stderr.contains("dumpo: include \"*.md\" matched 1 files\n"));
+    }
+
+    #[test]
+    fn fail_on_truncate_errors_after_writing_dump() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", &"x".repeat(2_000));
+
+        let args = PackArgs {
+            no_config: true,
+            max_total_bytes: Some(200),
+            fail_on_truncate: true,
+            ..base_args(&repo)
+        };
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let mut diag = Diagnostics {
+            out: &mut stderr,
+            verbose: false,
+            quiet: false,
+            color: false,
+        };
+        let err = pack_with(args.clone(), &mut stdout, copy_to_clipboard, &mut diag).unwrap_err();
+
+        assert!(err.is::<DumpTruncated>());
+        assert!(err.to_string().contains("max_total_bytes reached"));
+        assert!(!stdout.is_empty());
+
+        let roomy = PackArgs {
+            max_total_bytes: Some(200_000),
+            ..args
+        };
+        run_captured(roomy, false);
+    }
+
+    #[test]
+    fn config_max_files_error_mode_aborts_when_exceeded() {
+        let repo = TempRepo::new();
+        repo.write("dumpo.toml", "max_files = 2\nmax_files_mode = \"error\"\n");
+        repo.write("a.rs", "a\n");
+        repo.write("b.rs", "b\n");
+        repo.write("c.rs", "c\n");
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let mut diag = Diagnostics {
+            out: &mut stderr,
+            verbose: false,
+            quiet: false,
+            color: false,
+        };
+        let err =
+            pack_with(base_args(&repo), &mut stdout, copy_to_clipboard, &mut diag).unwrap_err();
+
+        assert!(err.to_string().contains("more than max_files = 2"));
+        assert!(stdout.is_empty());
+
+        // CLI wins over config: the default truncate mode keeps the first files and says so.
+        let args = PackArgs {
+            max_files_mode: Some(crate::dump::MaxFilesMode::Truncate),
+            ..base_args(&repo)
+        };
+        let (stdout, _stderr) = run_captured(args, false);
+        assert!(stdout.contains("## b.rs"));
+        assert!(!stdout.contains("## c.rs"));
+        assert!(stdout.ends_with(&fmt::max_files_footer(2)));
+    }
+
+    #[test]
+    fn since_dumps_only_files_changed_since_the_ref() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+        repo.write("src/main.rs", "fn main() {}\n");
+        repo.write("README.md", "readme\n");
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(repo.path())
+                .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {args:?}");
+        };
+        git(&["init", "-q"]);
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "init"]);
+        repo.write("src/lib.rs", "pub fn lib() -> u8 { 1 }\n");
+        repo.write("README.md", "changed readme\n");
+
+        let args = PackArgs {
+            no_config: true,
+            since: Some("HEAD".to_string()),
+            include: vec!["src/**".to_string()],
+            ..base_args(&repo)
+        };
+        let (stdout, _) = run_captured(args.clone(), false);
+        let headings: Vec<&str> = stdout.lines().filter(|l| l.starts_with("## ")).collect();
+        assert_eq!(headings, ["## src/lib.rs"]);
+
+        let bad_ref = PackArgs {
+            since: Some("no-such-ref".to_string()),
+            ..args
+        };
+        let mut diag = Diagnostics {
+            out: &mut io::sink(),
+            verbose: false,
+            quiet: false,
+            color: false,
+        };
+        let err = pack_with(bad_ref, &mut Vec::new(), copy_to_clipboard, &mut diag).unwrap_err();
+        assert!(err.to_string().contains("--since no-such-ref"));
+    }
+
+    #[test]
+    fn json_dump_and_manifest_carry_the_schema_version() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+        let out = TempRepo::new();
+        let manifest_path = out.path().join("manifest.json");
+
+        let args = PackArgs {
+            no_config: true,
+            format: Some(fmt::OutputFormat::Json),
+            manifest: Some(manifest_path.clone()),
+            ..base_args(&repo)
+        };
+        let (stdout, _) = run_captured(args, false);
+
+        let dump: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let manifest: serde_json::Value =
+            serde_json::from_slice(&fs::read(&manifest_path).unwrap()).unwrap();
+        for v in [&dump, &manifest] {
+            assert_eq!(v["schema_version"], fmt::SCHEMA_VERSION);
+        }
+        assert_eq!(manifest["files"][0]["path"], "src/lib.rs");
+    }
+
+    #[test]
+    fn plain_secrets_warning_lists_protected_files() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+        repo.write(".env", "SECRET=1\n");
+        repo.write("certs/server.pem", "-----BEGIN-----\n");
+        repo.write(".eslintrc", "{}\n");
+        repo.write(".config/.env.local", "SECRET=2\n");
+
+        let args = PackArgs {
+            no_config: true,
+            plain_secrets_warning: true,
+            ..base_args(&repo)
+        };
+        let (stdout, stderr) = run_captured(args.clone(), false);
+
+        assert!(
+            stderr.contains(
+                "dumpo: protected 3 secret files: .config/.env.local, .env, certs/server.pem\n"
+            ),
+            "{stderr}"
+        );
+        assert!(!stdout.contains("SECRET"));
+        assert!(!stdout.contains(".eslintrc"));
+        assert!(stdout.contains("## src/lib.rs"));
+
+        let (_, stderr) = run_captured(
+            PackArgs {
+                plain_secrets_warning: false,
+                ..args
+            },
+            false,
+        );
+        assert!(!stderr.contains("protected"));
+    }
+
+    #[test]
+    fn include_and_exclude_patterns_can_come_from_files() {
+        let repo = TempRepo::new();
+        repo.write("src/lib.rs", "pub fn lib() {}\n");
+        repo.write("src/gen/out.rs", "generated\n");
+        repo.write("docs/guide.md", "guide\n");
+        repo.write("tests/it.rs", "it\n");
+        let patterns = TempRepo::new();
+        patterns.write("include.txt", "# sources\nsrc/**\n\n  docs/**  \n");
+        patterns.write("exclude.txt", "src/gen/**\n");
+
+        let args = PackArgs {
+            no_config: true,
+            include: vec!["tests/**".to_string()],
+            include_from: Some(patterns.path().join("include.txt")),
+            exclude_from: Some(patterns.path().join("exclude.txt")),
+            ..base_args(&repo)
+        };
+        let (stdout, _) = run_captured(args, false);
+
+        let headings: Vec<&str> = stdout.lines().filter(|l| l.starts_with("## ")).collect();
+        assert_eq!(
+            headings,
+            ["## docs/guide.md", "## src/lib.rs", "## tests/it.rs"]
+        );
+    }
+
+    #[test]
+    fn dumpoinclude_allowlist_unions_with_cli_includes() {
+        let repo = TempRepo::new();
+        repo.write(".dumpoinclude", "# only sources\nsrc/**\n");
+        repo.write("src/lib.rs", "lib\n");
+        repo.write("src/nested/mod.rs", "mod\n");
+        repo.write("README.md", "readme\n");
+        repo.write("build.rs", "build\n");
+
+        let (stdout, _stderr) = run_captured(base_args(&repo), false);
+        let headings: Vec<&str> = stdout
+            .lines()
+            .filter_map(|l| l.strip_prefix("## "))
+            .collect();
+        assert_eq!(headings, ["src/lib.rs", "src/nested/mod.rs"]);
+
+        let args = PackArgs {
+            include: vec!["README.md".to_string()],
+            ..base_args(&repo)
+        };
+        let (stdout, _stderr) = run_captured(args, false);
+        assert!(stdout.contains("## README.md"));
+        assert!(stdout.contains("## src/lib.rs"));
+        assert!(!stdout.contains("## build.rs"));
+    }
+
+    #[test]
+    fn dumpoinclude_above_the_root_matches_relative_to_its_own_directory() {
+        let repo = TempRepo::new();
+        repo.write(".dumpoinclude", "src/lib.rs\nsrc/nested/**\n");
+        repo.write("src/lib.rs", "lib\n");
+        repo.write("src/nested/mod.rs", "mod\n");
+        repo.write("src/other.rs", "other\n");
+
+        let args = PackArgs {
+            path: repo.path().join("src"),
+            ..base_args(&repo)
+        };
+        let (stdout, _stderr) = run_captured(args, false);
+        let headings: Vec<&str> = stdout
+            .lines()
+            .filter_map(|l| l.strip_prefix("## "))
+            .collect();
+        assert_eq!(headings, ["lib.rs", "nested/mod.rs"]);
+    }
 }
@@ -0,0 +1,268 @@
+use crate::pack::{candidate_files, run_pack};
+use crate::selector::rel_slash;
+use crate::PackArgs;
+use anyhow::{Context, Result};
+use crossterm::cursor;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{self, ClearType};
+use crossterm::{execute, queue};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+pub(crate) fn run_pick(args: PackArgs) -> Result<()> {
+    let files = candidate_files(&args)?;
+    if files.is_empty() {
+        anyhow::bail!("no files to pick from in '{}'", args.path.display());
+    }
+
+    match pick_interactively(files)? {
+        Some(selection) => run_pack(selection_args(args, &selection)?),
+        None => Ok(()),
+    }
+}
+
+// Rewrites `args` so `pack` dumps exactly `selection`: each path becomes a literal include glob.
+// The other filters stay in place; the picked files already passed them.
+pub(crate) fn selection_args(args: PackArgs, selection: &[PathBuf]) -> Result<PackArgs> {
+    if selection.is_empty() {
+        anyhow::bail!("no files selected");
+    }
+
+    let include = selection.iter().map(|rel| literal_glob(rel)).collect();
+    Ok(PackArgs {
+        include,
+        // A pattern file would add its matches back in.
+        include_from: None,
+        force_include: Vec::new(),
+        skip_include_file: true,
+        ..args
+    })
+}
+
+// A glob matching exactly `rel`. globset::escape leaves `!` alone, but a leading one
+// would make the include an exclude, so it goes in a one-way alternation.
+fn literal_glob(rel: &Path) -> String {
+    let escaped = globset::escape(&rel_slash(rel));
+    match escaped.strip_prefix('!') {
+        Some(rest) => format!("{{!}}{rest}"),
+        None => escaped,
+    }
+}
+
+// Runs the checklist on stderr (so `--stdout` can still be piped); None means cancelled.
+fn pick_interactively(files: Vec<PathBuf>) -> Result<Option<Vec<PathBuf>>> {
+    let mut picker = Picker::new(files);
+    let mut err = io::stderr();
+    let _guard = TerminalGuard::enter(&mut err)?;
+
+    loop {
+        let (_, rows) = terminal::size().context("failed to read terminal size")?;
+        picker.render(&mut err, rows)?;
+
+        if let Event::Key(key) = event::read().context("failed to read terminal input")? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match picker.handle(key) {
+                Step::Continue => {}
+                Step::Confirm => return Ok(Some(picker.selection())),
+                Step::Cancel => return Ok(None),
+            }
+        }
+    }
+}
+
+// Restores the terminal on every exit path, errors and panics included.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter(out: &mut impl Write) -> Result<Self> {
+        terminal::enable_raw_mode().context("failed to enable raw terminal mode")?;
+        let guard = Self;
+        execute!(out, terminal::EnterAlternateScreen, cursor::Hide)
+            .context("failed to set up the terminal")?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stderr(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Continue,
+    Confirm,
+    Cancel,
+}
+
+struct Picker {
+    files: Vec<PathBuf>,
+    checked: Vec<bool>,
+    cursor: usize,
+    // First visible row, so the cursor stays on screen in long lists.
+    top: usize,
+}
+
+impl Picker {
+    fn new(files: Vec<PathBuf>) -> Self {
+        let checked = vec![false; files.len()];
+        Self {
+            files,
+            checked,
+            cursor: 0,
+            top: 0,
+        }
+    }
+
+    fn handle(&mut self, key: KeyEvent) -> Step {
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Step::Cancel,
+            KeyCode::Esc | KeyCode::Char('q') => Step::Cancel,
+            KeyCode::Enter => Step::Confirm,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.cursor = self.cursor.saturating_sub(1);
+                Step::Continue
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.cursor = (self.cursor + 1).min(self.files.len() - 1);
+                Step::Continue
+            }
+            KeyCode::Char(' ') => {
+                self.checked[self.cursor] = !self.checked[self.cursor];
+                Step::Continue
+            }
+            // Check everything, or clear everything if it already is.
+            KeyCode::Char('a') => {
+                let all = self.checked.iter().all(|&c| c);
+                self.checked.fill(!all);
+                Step::Continue
+            }
+            _ => Step::Continue,
+        }
+    }
+
+    fn selection(&self) -> Vec<PathBuf> {
+        self.files
+            .iter()
+            .zip(&self.checked)
+            .filter(|(_, &checked)| checked)
+            .map(|(f, _)| f.clone())
+            .collect()
+    }
+
+    fn render(&mut self, out: &mut impl Write, rows: u16) -> Result<()> {
+        // One row for the help line.
+        let visible = usize::from(rows).saturating_sub(1).max(1);
+        if self.cursor < self.top {
+            self.top = self.cursor;
+        } else if self.cursor >= self.top + visible {
+            self.top = self.cursor + 1 - visible;
+        }
+
+        let selected = self.checked.iter().filter(|&&c| c).count();
+        queue!(
+            out,
+            terminal::Clear(ClearType::All),
+            cursor::MoveTo(0, 0),
+            Print(format!(
+                "dumpo pick: space toggle, a all, enter dump, q quit ({selected}/{} selected)",
+                self.files.len()
+            )),
+        )?;
+
+        let end = (self.top + visible).min(self.files.len());
+        for (row, i) in (self.top..end).enumerate() {
+            let pointer = if i == self.cursor { '>' } else { ' ' };
+            let mark = if self.checked[i] { 'x' } else { ' ' };
+            queue!(
+                out,
+                cursor::MoveTo(0, (row + 1) as u16),
+                Print(format!("{pointer} [{mark}] {}", self.files[i].display())),
+            )?;
+        }
+
+        out.flush().context("failed writing to stderr")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dump::{build_dump_bytes, DumpOptions};
+    use crate::selector::Selector;
+    use crate::testutil::TempRepo;
+
+    #[test]
+    fn selection_args_dump_exactly_the_selected_files() {
+        let repo = TempRepo::new();
+        repo.write("src/a[1].rs", "a\n");
+        repo.write("src/a1.rs", "a1\n");
+        repo.write("src/b.rs", "b\n");
+        repo.write("README.md", "readme\n");
+        repo.write("!notes.md", "notes\n");
+
+        let args = PackArgs {
+            path: repo.path().to_path_buf(),
+            ..Default::default()
+        };
+        let selection = [
+            PathBuf::from("src/a[1].rs"),
+            PathBuf::from("README.md"),
+            PathBuf::from("!notes.md"),
+        ];
+        let args = selection_args(args, &selection).unwrap();
+
+        let selector = Selector::for_root(repo.path(), &args.include, &args.exclude).unwrap();
+        let opts = DumpOptions::new(10_000, 200_000, false);
+        let (out, _) = build_dump_bytes(repo.path(), &opts, &selector).unwrap();
+        let s = String::from_utf8(out).unwrap();
+
+        let headings: Vec<&str> = s.lines().filter_map(|l| l.strip_prefix("## ")).collect();
+        assert_eq!(headings, ["!notes.md", "README.md", "src/a[1].rs"]);
+
+        let with_file = PackArgs {
+            path: repo.path().to_path_buf(),
+            include_from: Some(repo.path().join("pats.txt")),
+            ..Default::default()
+        };
+        let args = selection_args(with_file, &selection).unwrap();
+        assert_eq!(args.include_from, None);
+
+        let forced = PackArgs {
+            force_include: vec!["*.md".to_string()],
+            ..Default::default()
+        };
+        assert!(selection_args(forced, &selection)
+            .unwrap()
+            .force_include
+            .is_empty());
+
+        assert!(selection_args(PackArgs::default(), &[]).is_err());
+    }
+
+    #[test]
+    fn picker_toggles_and_confirms_checked_files() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut picker = Picker::new(vec!["a".into(), "b".into(), "c".into()]);
+
+        assert_eq!(picker.handle(key(KeyCode::Down)), Step::Continue);
+        picker.handle(key(KeyCode::Char(' ')));
+        picker.handle(key(KeyCode::Down));
+        picker.handle(key(KeyCode::Down));
+        picker.handle(key(KeyCode::Char(' ')));
+        assert_eq!(picker.selection(), [PathBuf::from("b"), PathBuf::from("c")]);
+
+        picker.handle(key(KeyCode::Char('a')));
+        assert_eq!(picker.selection().len(), 3);
+        picker.handle(key(KeyCode::Char('a')));
+        assert!(picker.selection().is_empty());
+
+        assert_eq!(picker.handle(key(KeyCode::Enter)), Step::Confirm);
+        assert_eq!(picker.handle(key(KeyCode::Esc)), Step::Cancel);
+    }
+}
@@ -1,42 +1,211 @@
 use anyhow::{Context, Result};
 use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::cell::RefCell;
+use std::path::Path;
 
 #[derive(Debug, Clone)]
 pub(crate) struct Selector {
-    include: Option<GlobSet>, // None means "include all"
-    exclude: Option<GlobSet>, // None means "exclude nothing"
+    include: Vec<PatternSet>, // empty means "include all"
+    exclude: Vec<PatternSet>, // empty means "exclude nothing"
+}
+
+// The compiled set plus the source patterns, so hits can be attributed per pattern.
+#[derive(Debug, Clone)]
+struct PatternSet {
+    patterns: Vec<String>,
+    set: GlobSet,
+    hits: RefCell<Vec<usize>>,
+    // Prepended (with a '/') to root-relative paths before matching; empty for patterns
+    // written relative to the walk root.
+    base: String,
+}
+
+impl PatternSet {
+    fn new(flag: &str, patterns: &[String], base: &str) -> Result<Self> {
+        Ok(Self {
+            patterns: patterns.to_vec(),
+            set: build_globset(flag, patterns)?,
+            hits: RefCell::new(vec![0; patterns.len()]),
+            base: base.to_string(),
+        })
+    }
+
+    // Indices of the patterns matching a root-relative path.
+    fn matching(&self, rel_path_slash: &str) -> Vec<usize> {
+        if self.base.is_empty() {
+            self.set.matches(rel_path_slash)
+        } else {
+            self.set.matches(format!("{}/{rel_path_slash}", self.base))
+        }
+    }
+
+    fn is_match_counted(&self, rel_path_slash: &str) -> bool {
+        let matched = self.matching(rel_path_slash);
+        let mut hits = self.hits.borrow_mut();
+        for &i in &matched {
+            hits[i] += 1;
+        }
+        !matched.is_empty()
+    }
+
+    fn counts(&self) -> Vec<(String, usize)> {
+        self.patterns
+            .iter()
+            .cloned()
+            .zip(self.hits.borrow().iter().copied())
+            .collect()
+    }
+}
+
+// How many of the paths tested so far each pattern matched.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct SelectorReport {
+    pub(crate) include: Vec<(String, usize)>,
+    pub(crate) exclude: Vec<(String, usize)>,
 }
 
 impl Selector {
     pub(crate) fn new(includes: &[String], excludes: &[String]) -> Result<Self> {
-        let include = if includes.is_empty() {
-            None
-        } else {
-            Some(build_globset("--include", includes)?)
-        };
+        let (includes, excludes) = negated_includes_to_excludes(includes, excludes);
+        let mut selector = Self::empty();
+        selector.add(&includes, &excludes, "")?;
+        Ok(selector)
+    }
 
-        let exclude = if excludes.is_empty() {
-            None
-        } else {
-            Some(build_globset("--exclude", excludes)?)
-        };
+    fn empty() -> Self {
+        Self {
+            include: Vec::new(),
+            exclude: Vec::new(),
+        }
+    }
+
+    // Like `new`, but patterns naming a directory under `root` (or ending in `/`)
+    // select everything beneath it, so `--include src` means `src/**`.
+    pub(crate) fn for_root(root: &Path, includes: &[String], excludes: &[String]) -> Result<Self> {
+        let (includes, excludes) = negated_includes_to_excludes(includes, excludes);
+        let mut selector = Self::empty();
+        selector.add(
+            &expand_dir_shorthand(root, &includes),
+            &expand_dir_shorthand(root, &excludes),
+            "",
+        )?;
+        Ok(selector)
+    }
+
+    // Adds patterns written relative to `dir` (a config file's directory), where the walk
+    // root is the subdirectory `base` of `dir`: paths are matched as `base/<rel>`.
+    pub(crate) fn add_relative_to(
+        &mut self,
+        dir: &Path,
+        base: &str,
+        includes: &[String],
+        excludes: &[String],
+    ) -> Result<()> {
+        let (includes, excludes) = negated_includes_to_excludes(includes, excludes);
+        self.add(
+            &expand_dir_shorthand(dir, &includes),
+            &expand_dir_shorthand(dir, &excludes),
+            base,
+        )
+    }
 
-        Ok(Self { include, exclude })
+    // Takes the lists as they are: callers have already moved `!` includes to the excludes.
+    fn add(&mut self, includes: &[String], excludes: &[String], base: &str) -> Result<()> {
+        if !includes.is_empty() {
+            self.include
+                .push(PatternSet::new("--include", includes, base)?);
+        }
+        if !excludes.is_empty() {
+            self.exclude
+                .push(PatternSet::new("--exclude", excludes, base)?);
+        }
+        Ok(())
     }
 
     pub(crate) fn matches(&self, rel_path_slash: &str) -> bool {
-        let included = match &self.include {
-            None => true,
-            Some(set) => set.is_match(rel_path_slash),
+        // Every set is tested (no short-circuit) so the per-pattern hit counts stay complete.
+        let hits = |sets: &[PatternSet]| {
+            sets.iter()
+                .filter(|s| s.is_match_counted(rel_path_slash))
+                .count()
         };
 
-        let not_excluded = match &self.exclude {
-            None => true,
-            Some(set) => !set.is_match(rel_path_slash),
-        };
+        let included = self.include.is_empty() || hits(&self.include) > 0;
+        let not_excluded = hits(&self.exclude) == 0;
 
         included && not_excluded
     }
+
+    // Position (across all include sets, in the order given) of the first include pattern
+    // matching the path; None without includes or when none match. Hit counts are untouched.
+    pub(crate) fn include_index(&self, rel_path_slash: &str) -> Option<usize> {
+        let mut offset = 0;
+        for set in &self.include {
+            if let Some(i) = set.matching(rel_path_slash).into_iter().min() {
+                return Some(offset + i);
+            }
+            offset += set.patterns.len();
+        }
+        None
+    }
+
+    // Zeroes the hit counts, before walking the same files a second time.
+    pub(crate) fn reset_counts(&self) {
+        for set in self.include.iter().chain(&self.exclude) {
+            set.hits.borrow_mut().fill(0);
+        }
+    }
+
+    pub(crate) fn report(&self) -> SelectorReport {
+        SelectorReport {
+            include: self.include.iter().flat_map(PatternSet::counts).collect(),
+            exclude: self.exclude.iter().flat_map(PatternSet::counts).collect(),
+        }
+    }
+}
+
+// The form paths are matched in: '/' separators, and any non-UTF-8 bytes replaced
+// with U+FFFD (the same lossy text headings show). See --skip-non-utf8-paths.
+pub(crate) fn rel_slash(rel: &Path) -> String {
+    rel.to_string_lossy().replace('\\', "/")
+}
+
+// An include written `!pattern` means "everything except pattern": it joins the excludes,
+// and if every include is negated, nothing restricts what's included.
+fn negated_includes_to_excludes(
+    includes: &[String],
+    excludes: &[String],
+) -> (Vec<String>, Vec<String>) {
+    let mut kept = Vec::new();
+    let mut excludes = excludes.to_vec();
+    for p in includes {
+        match p.strip_prefix('!') {
+            Some(negated) => excludes.push(negated.to_string()),
+            None => kept.push(p.clone()),
+        }
+    }
+    (kept, excludes)
+}
+
+fn expand_dir_shorthand(root: &Path, patterns: &[String]) -> Vec<String> {
+    patterns
+        .iter()
+        .map(|p| {
+            let trimmed = p.trim_end_matches('/');
+            let names_dir = trimmed.len() < p.len()
+                || (!has_glob_meta(p) && !p.is_empty() && root.join(p).is_dir());
+
+            if names_dir && !trimmed.is_empty() {
+                format!("{trimmed}/**")
+            } else {
+                p.clone()
+            }
+        })
+        .collect()
+}
+
+fn has_glob_meta(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '[', ']', '{', '}'])
 }
 
 fn build_globset(flag: &str, patterns: &[String]) -> Result<GlobSet> {
@@ -48,3 +217,112 @@ fn build_globset(flag: &str, patterns: &[String]) -> Result<GlobSet> {
     b.build()
         .with_context(|| format!("{flag}: failed to build glob set"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // globset expands `{a,b}` alternations itself (nesting included), so no pre-expansion is needed.
+    #[test]
+    fn brace_alternation_matches_each_branch() {
+        let s = Selector::new(&["src/**/*.{rs,toml}".to_string()], &[]).unwrap();
+
+        assert!(s.matches("src/a/lib.rs"));
+        assert!(s.matches("src/Cargo.toml"));
+        assert!(!s.matches("src/README.md"));
+
+        let nested = Selector::new(&["src/{a,b/{c,d}}/*.rs".to_string()], &[]).unwrap();
+        assert!(nested.matches("src/a/x.rs"));
+        assert!(nested.matches("src/b/d/x.rs"));
+        assert!(!nested.matches("src/b/x.rs"));
+    }
+
+    #[test]
+    fn include_index_is_the_first_matching_pattern() {
+        let patterns = ["b.rs", "src/**", "*.rs"].map(String::from);
+        let s = Selector::new(&patterns, &[]).unwrap();
+
+        assert_eq!(s.include_index("b.rs"), Some(0));
+        assert_eq!(s.include_index("src/a.rs"), Some(1));
+        assert_eq!(s.include_index("a.rs"), Some(2));
+        assert_eq!(s.include_index("README.md"), None);
+        assert_eq!(s.report().include.iter().map(|(_, n)| n).sum::<usize>(), 0);
+    }
+
+    #[test]
+    fn negated_include_means_everything_except() {
+        let s = Selector::new(&["!tests/**".to_string()], &[]).unwrap();
+        assert!(s.matches("src/lib.rs"));
+        assert!(s.matches("Cargo.toml"));
+        assert!(!s.matches("tests/it.rs"));
+
+        // Alongside plain includes and --exclude, it narrows them further.
+        let s = Selector::new(
+            &["src/**".to_string(), "!src/gen/**".to_string()],
+            &["**/*.md".to_string()],
+        )
+        .unwrap();
+        assert!(s.matches("src/lib.rs"));
+        assert!(!s.matches("src/gen/out.rs"));
+        assert!(!s.matches("src/README.md"));
+        assert!(!s.matches("tests/it.rs"));
+    }
+
+    #[test]
+    fn directory_shorthand_selects_everything_beneath() {
+        let repo = crate::testutil::TempRepo::new();
+        repo.write("src/lib.rs", "x");
+        repo.write("src/nested/mod.rs", "x");
+        repo.write("docs/guide.md", "x");
+        repo.write("Cargo.toml", "x");
+
+        let s = Selector::for_root(repo.path(), &["src".to_string()], &[]).unwrap();
+        assert!(s.matches("src/lib.rs"));
+        assert!(s.matches("src/nested/mod.rs"));
+        assert!(!s.matches("Cargo.toml"));
+
+        let s = Selector::for_root(repo.path(), &[], &["docs/".to_string()]).unwrap();
+        assert!(!s.matches("docs/guide.md"));
+        assert!(s.matches("src/lib.rs"));
+
+        // Literal file patterns are left alone.
+        let s = Selector::for_root(repo.path(), &["Cargo.toml".to_string()], &[]).unwrap();
+        assert!(s.matches("Cargo.toml"));
+        assert!(!s.matches("src/lib.rs"));
+    }
+
+    #[test]
+    fn report_counts_hits_per_pattern() {
+        let s = Selector::new(
+            &["src/**".to_string(), "**/*.rs".to_string()],
+            &["**/gen.rs".to_string()],
+        )
+        .unwrap();
+
+        for path in [
+            "src/lib.rs",
+            "src/README.md",
+            "tests/it.rs",
+            "src/gen.rs",
+            "notes.txt",
+        ] {
+            s.matches(path);
+        }
+
+        let report = s.report();
+        assert_eq!(
+            report.include,
+            vec![("src/**".to_string(), 3), ("**/*.rs".to_string(), 3)]
+        );
+        assert_eq!(report.exclude, vec![("**/gen.rs".to_string(), 1)]);
+    }
+
+    #[test]
+    fn unclosed_brace_is_reported_with_flag_and_pattern() {
+        let err = Selector::new(&["src/{a,b".to_string()], &[]).unwrap_err();
+        let msg = format!("{err:#}");
+
+        assert!(msg.starts_with("--include: invalid glob pattern: \"src/{a,b\""));
+        assert!(msg.contains("unclosed alternate group"));
+    }
+}
@@ -0,0 +1,38 @@
+// Minimal ANSI styling for stderr; every helper is a no-op when color is off.
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+const BOLD_CYAN: &str = "\x1b[1;36m";
+
+pub(crate) fn dim(s: &str, color: bool) -> String {
+    paint(DIM, s, color)
+}
+
+pub(crate) fn highlight(s: &str, color: bool) -> String {
+    paint(BOLD_CYAN, s, color)
+}
+
+fn paint(code: &str, s: &str, color: bool) -> String {
+    if color {
+        format!("{code}{s}{RESET}")
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn helpers_are_plain_when_color_is_off() {
+        assert_eq!(dim("label", false), "label");
+        assert_eq!(highlight("value", false), "value");
+    }
+
+    #[test]
+    fn helpers_wrap_in_escape_codes_when_color_is_on() {
+        assert_eq!(dim("label", true), "\x1b[2mlabel\x1b[0m");
+        assert_eq!(highlight("value", true), "\x1b[1;36mvalue\x1b[0m");
+    }
+}
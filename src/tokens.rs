@@ -0,0 +1,19 @@
+// Rough token estimate for budgeting: about four bytes of source text per token
+// for common BPE tokenizers. Good enough to answer "does this fit?", not exact.
+pub(crate) const BYTES_PER_TOKEN: usize = 4;
+
+pub(crate) fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(BYTES_PER_TOKEN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimate_tokens_rounds_up() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abc"), 1);
+        assert_eq!(estimate_tokens("abcdefghi"), 3);
+    }
+}
@@ -0,0 +1,45 @@
+const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+// Decimal units (1 KB = 1000 B) with one decimal place, e.g. "1.5 MB"; exact below 1 KB.
+pub(crate) fn human_bytes(n: usize) -> String {
+    if n < 1000 {
+        return format!("{n} B");
+    }
+
+    let mut value = n as f64 / 1000.0;
+    let mut unit = 0;
+    // Step up once rounding to one decimal would print "1000.0".
+    while value >= 999.95 && unit + 1 < UNITS.len() {
+        value /= 1000.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+// `human_bytes`, or the raw count for machine-readable output (--exact-bytes).
+pub(crate) fn format_bytes(n: usize, exact: bool) -> String {
+    if exact {
+        format!("{n} bytes")
+    } else {
+        human_bytes(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn human_bytes_boundaries() {
+        assert_eq!(human_bytes(0), "0 B");
+        assert_eq!(human_bytes(999), "999 B");
+        assert_eq!(human_bytes(1000), "1.0 KB");
+        assert_eq!(human_bytes(1049), "1.0 KB");
+        assert_eq!(human_bytes(400_000), "400.0 KB");
+        assert_eq!(human_bytes(999_999), "1.0 MB");
+        assert_eq!(human_bytes(1_500_000), "1.5 MB");
+        assert_eq!(human_bytes(2_000_000_000), "2.0 GB");
+
+        assert_eq!(format_bytes(400_000, true), "400000 bytes");
+    }
+}
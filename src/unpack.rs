@@ -0,0 +1,140 @@
+use crate::archive::member_path;
+use crate::config::DumpoConfig;
+use crate::format as fmt;
+use crate::UnpackArgs;
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+pub(crate) fn run_unpack(args: UnpackArgs) -> Result<()> {
+    let cfg = if args.no_config {
+        DumpoConfig::default()
+    } else {
+        let cwd = std::env::current_dir().context("failed to read current directory")?;
+        DumpoConfig::load_nearest(&cwd)?.1
+    };
+    let prefix = args
+        .file_heading_prefix
+        .or(cfg.file_heading_prefix)
+        .unwrap_or_else(|| fmt::DEFAULT_FILE_HEADING_PREFIX.to_string());
+    fmt::validate_file_heading_prefix(&prefix)?;
+
+    let dump = match &args.input {
+        Some(path) => fs::read_to_string(path)
+            .with_context(|| format!("failed to read dump: {}", path.display()))?,
+        None => {
+            let mut s = String::new();
+            io::stdin()
+                .read_to_string(&mut s)
+                .context("failed reading stdin")?;
+            s
+        }
+    };
+
+    let files = parse_dump(&dump, &prefix);
+    for (rel, content) in &files {
+        let dest = args.out.join(rel);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::write(&dest, content).with_context(|| format!("failed to write {}", dest.display()))?;
+    }
+
+    eprintln!(
+        "dumpo: unpacked {} files into {}",
+        files.len(),
+        args.out.display()
+    );
+    Ok(())
+}
+
+// Files in a markdown dump, in order. A file runs from its heading to the next one and
+// its content ends at the last closing fence, so fences inside the content survive.
+// Sections without a code fence (symlink notes, the binary listing, ...) and paths that
+// would escape the output directory are skipped.
+pub(crate) fn parse_dump(dump: &str, prefix: &str) -> Vec<(PathBuf, String)> {
+    let mut sections: Vec<(&str, Vec<&str>)> = Vec::new();
+    for line in dump.split_inclusive('\n') {
+        if let Some(name) = line.strip_prefix(prefix) {
+            sections.push((name.trim_end_matches(['\n', '\r']), Vec::new()));
+        } else if let Some((_, lines)) = sections.last_mut() {
+            lines.push(line);
+        }
+    }
+
+    sections
+        .into_iter()
+        .filter_map(|(name, lines)| {
+            let rel = member_path(name.as_ref())?;
+            let content = fenced_content(&lines)?;
+            Some((rel, content))
+        })
+        .collect()
+}
+
+fn fenced_content(lines: &[&str]) -> Option<String> {
+    let open = lines.iter().position(|l| !l.trim().is_empty())?;
+    let fence = ["```", "~~~"]
+        .into_iter()
+        .find(|f| lines[open].starts_with(f))?;
+    let close = lines.iter().rposition(|l| l.trim_end() == fence)?;
+    if close <= open {
+        return None;
+    }
+
+    let content = lines[open + 1..close].concat();
+    if content.trim_end() == fmt::EMPTY_FILE_NOTE {
+        return Some(String::new());
+    }
+    Some(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dump::{build_dump_bytes, DumpOptions};
+    use crate::selector::Selector;
+    use crate::testutil::TempRepo;
+
+    #[test]
+    fn custom_heading_prefix_round_trips_through_unpack() {
+        let repo = TempRepo::new();
+        let files = [
+            (
+                "README.md",
+                "# Title\n\n## Usage\n\n```bash\ndumpo pack\n```\n",
+            ),
+            ("src/lib.rs", "pub fn lib() {}\n"),
+            ("src/empty.rs", ""),
+        ];
+        for (rel, content) in files {
+            repo.write(rel, content);
+        }
+
+        let prefix = "### FILE: ";
+        let opts = DumpOptions {
+            file_heading_prefix: prefix.to_string(),
+            ..DumpOptions::new(10_000, 200_000, false)
+        };
+        let selector = Selector::new(&[], &[]).unwrap();
+        let (out, _) = build_dump_bytes(repo.path(), &opts, &selector).unwrap();
+        let s = String::from_utf8(out).unwrap();
+
+        let headings: Vec<&str> = s.lines().filter_map(|l| l.strip_prefix(prefix)).collect();
+        assert_eq!(headings, ["README.md", "src/empty.rs", "src/lib.rs"]);
+
+        let mut unpacked = parse_dump(&s, prefix);
+        unpacked.sort();
+        let mut expected: Vec<(PathBuf, String)> = files
+            .iter()
+            .map(|(rel, content)| (PathBuf::from(rel), content.to_string()))
+            .collect();
+        expected.sort();
+        assert_eq!(unpacked, expected);
+
+        assert!(fmt::validate_file_heading_prefix("").is_err());
+        assert!(fmt::validate_file_heading_prefix("```").is_err());
+    }
+}
@@ -0,0 +1,33 @@
+use anyhow::{Context, Result};
+use std::io::{self, Write};
+
+pub(crate) fn run_version() -> Result<()> {
+    let mut out = io::stdout().lock();
+    out.write_all(version_text().as_bytes())
+        .and_then(|()| out.flush())
+        .context("failed writing to stdout")
+}
+
+fn version_text() -> String {
+    format!(
+        "dumpo {}\ncommit: {}\ntarget: {}\nprofile: {}\n",
+        env!("CARGO_PKG_VERSION"),
+        env!("DUMPO_GIT_SHA"),
+        env!("DUMPO_TARGET"),
+        env!("DUMPO_PROFILE"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_text_includes_package_version_and_build_info() {
+        let s = version_text();
+
+        assert!(s.starts_with(&format!("dumpo {}\n", env!("CARGO_PKG_VERSION"))));
+        assert!(s.contains("commit: "));
+        assert!(s.contains("target: "));
+    }
+}